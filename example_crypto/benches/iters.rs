@@ -0,0 +1,30 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use example_crypto::iters::{EndlessIter, EndlessShuffledIter};
+use std::hint::black_box;
+
+const SIZES: [usize; 4] = [16, 256, 4096, 65536];
+const SHUFFLE_FACTOR: usize = 4;
+
+fn bench_iters(c: &mut Criterion) {
+    for size in SIZES {
+        let values: Vec<u64> = (0..size as u64).collect();
+
+        c.bench_function(format!("iters::endless::{size}").as_str(), |b| {
+            let mut iter = EndlessIter::new(values.clone());
+            b.iter(|| black_box(iter.next()))
+        });
+
+        c.bench_function(format!("iters::endless_shuffled::{size}").as_str(), |b| {
+            let mut iter = EndlessShuffledIter::new(values.clone(), SHUFFLE_FACTOR);
+            b.iter(|| black_box(iter.next()))
+        });
+    }
+}
+
+criterion_group! {
+    name = iters;
+    config = Criterion::default();
+    targets = bench_iters
+}
+
+criterion_main!(iters);
@@ -1,32 +1,77 @@
-use criterion::{criterion_group, criterion_main, Criterion};
-use example_crypto::openssl::keygen::{keygen_ec, keygen_ed25519, keygen_ed448, keygen_rsa, keygen_x25519, keygen_x448};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use example_crypto::openssl::client_ca::{ClientCAConfig, ClientCAExample, KeyAlgorithm};
+use example_crypto::openssl::keygen::{
+    keygen_ec, keygen_ed25519, keygen_ed448, keygen_rsa, keygen_x25519, keygen_x448,
+};
 use openssl::ec::EcGroup;
 use openssl::nid::Nid;
 
+/// RSA modulus sizes to benchmark, in bits.
+const RSA_KEY_SIZES: [u32; 3] = [2048, 3072, 4096];
+
+/// RSA key generation takes long enough that the default sample size makes the run drag on.
+const RSA_SAMPLE_SIZE: usize = 10;
+
 pub fn bench_keygen(c: &mut Criterion) {
     let secp256k1 = EcGroup::from_curve_name(Nid::SECP256K1).unwrap();
 
     // // eddsa
-    c.bench_function("openssl::keygen::ed25519", |b| b.iter(|| keygen_ed25519()));
-    c.bench_function("openssl::keygen::ed448", |b| b.iter(|| keygen_ed448()));
-    c.bench_function("openssl::keygen::x25519", |b| b.iter(|| keygen_x25519()));
-    c.bench_function("openssl::keygen::x448", |b| b.iter(|| keygen_x448()));
+    c.bench_function("openssl::keygen::ed25519", |b| {
+        b.iter(|| keygen_ed25519().unwrap())
+    });
+    c.bench_function("openssl::keygen::ed448", |b| {
+        b.iter(|| keygen_ed448().unwrap())
+    });
+    c.bench_function("openssl::keygen::x25519", |b| {
+        b.iter(|| keygen_x25519().unwrap())
+    });
+    c.bench_function("openssl::keygen::x448", |b| {
+        b.iter(|| keygen_x448().unwrap())
+    });
 
     // ecdsa
     c.bench_function("openssl::keygen::secp256", |b| {
-        b.iter(|| keygen_ec(&secp256k1))
+        b.iter(|| keygen_ec(&secp256k1).unwrap())
     });
 
     // rsa
-    c.bench_function("openssl::keygen::rsa2048", |b| b.iter(|| keygen_rsa(2048)));
-    c.bench_function("openssl::keygen::rsa3072", |b| b.iter(|| keygen_rsa(3072)));
-    c.bench_function("openssl::keygen::rsa4096", |b| b.iter(|| keygen_rsa(4096)));
+    let mut group = c.benchmark_group("openssl::keygen::rsa");
+    group.sample_size(RSA_SAMPLE_SIZE);
+
+    for bits in RSA_KEY_SIZES {
+        group.bench_with_input(BenchmarkId::from_parameter(bits), &bits, |b, &bits| {
+            b.iter(|| keygen_rsa(bits).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+/// Compare generating a CA's keys one after another against generating them concurrently.
+pub fn bench_client_ca(c: &mut Criterion) {
+    let config = || {
+        ClientCAConfig::builder()
+            .key_algo(KeyAlgorithm::rsa(2048))
+            .build()
+    };
+
+    let mut group = c.benchmark_group("openssl::client_ca::generate::rsa::2048");
+    group.sample_size(RSA_SAMPLE_SIZE);
+
+    group.bench_function("sequential", |b| {
+        b.iter(|| ClientCAExample::generate_sequential(config()).unwrap())
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| ClientCAExample::generate(config()).unwrap())
+    });
+
+    group.finish();
 }
 
-criterion_group!{
+criterion_group! {
     name = keygen;
     config = Criterion::default();
-    targets = bench_keygen
+    targets = bench_keygen, bench_client_ca
 }
 
-criterion_main!(keygen);
\ No newline at end of file
+criterion_main!(keygen);
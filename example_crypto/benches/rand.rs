@@ -1,23 +1,25 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use example_crypto::rng::{
     DevRandomBufRng, DevRandomDirectRng, DevUrandomBufRng, DevUrandomDirectRng, OpenSslRng,
-    RandCrateOs, RandCrateThread, RandGenerator, RandOsRng, RandThreadRng, SysRandomBuffered,
-    SysRandomDirect, SysUrandomBuffered, SysUrandomDirect,
+    PooledRng, RandCrateOs, RandCrateThread, RandGeneratorBenchmark, RandOsRng, RandThreadRng,
+    RngCoreGenerator, SysRandomBuffered, SysRandomDirect, SysUrandomBuffered, SysUrandomDirect,
 };
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::env;
 
 const BUFFER_SIZES: [usize; 9] = [32, 64, 128, 256, 512, 1024, 2048, 4096, 8192];
 
-pub struct RngBencher<R: RandGenerator> {
+pub struct RngBencher<R: RandGeneratorBenchmark> {
     rng: R,
-    prefix: &'static str,
 }
 
 impl<R> RngBencher<R>
 where
-    R: RandGenerator,
+    R: RandGeneratorBenchmark,
 {
-    pub fn new(rng: R, prefix: &'static str) -> Self {
-        Self { rng, prefix }
+    pub fn new(rng: R) -> Self {
+        Self { rng }
     }
 
     /// Conduct the benchmark using fixed-size arrays on the stack.
@@ -35,7 +37,7 @@ where
     }
 
     fn bench_arr_specific<const S: usize>(&mut self, c: &mut Criterion) {
-        c.bench_function(format!("{}::array::{}", self.prefix, S).as_str(), |b| {
+        c.bench_function(format!("{}::array::{}", self.rng.prefix(), S).as_str(), |b| {
             b.iter(|| self.rng.generate_array::<S>());
         });
     }
@@ -55,7 +57,7 @@ where
     }
 
     fn bench_vec_specific<const S: usize>(&mut self, c: &mut Criterion) {
-        c.bench_function(format!("{}::vec::{}", self.prefix, S).as_str(), |b| {
+        c.bench_function(format!("{}::vec::{}", self.rng.prefix(), S).as_str(), |b| {
             b.iter(|| self.rng.generate_vec::<S>());
         });
     }
@@ -63,47 +65,102 @@ where
 
 fn bench_fast_rands(c: &mut Criterion) {
     // buffered /dev/urandom
-    RngBencher::new(DevUrandomBufRng::new(), DevUrandomBufRng::PREFIX)
+    RngBencher::new(DevUrandomBufRng::new())
         .bench_arr(c)
         .bench_vec(c);
 
     // direct /dev/urandom
-    RngBencher::new(DevUrandomDirectRng::new(), DevUrandomDirectRng::PREFIX)
+    RngBencher::new(DevUrandomDirectRng::new())
         .bench_arr(c)
         .bench_vec(c);
 
     // openssl
-    RngBencher::new(OpenSslRng::new(), OpenSslRng::PREFIX)
+    RngBencher::new(OpenSslRng::new())
+        .bench_arr(c)
+        .bench_vec(c);
+
+    // openssl, drawn through a pool
+    RngBencher::new(PooledRng::new(OpenSslRng::new()))
         .bench_arr(c)
         .bench_vec(c);
 
     // rand: os rng
-    RngBencher::new(RandOsRng::new(), RandOsRng::PREFIX)
+    RngBencher::new(RandOsRng::new())
         .bench_arr(c)
         .bench_vec(c);
 
     // rand: thread rng
-    RngBencher::new(RandThreadRng::new(), RandThreadRng::PREFIX)
+    RngBencher::new(RandThreadRng::new())
+        .bench_arr(c)
+        .bench_vec(c);
+
+    // rand: std rng, seeded from the os
+    RngBencher::new(RngCoreGenerator::from_crypto_rng(StdRng::from_entropy()))
+        .bench_arr(c)
+        .bench_vec(c);
+
+    // rand_sys: buffered /dev/urandom
+    RngBencher::new(SysUrandomBuffered::new().unwrap())
+        .bench_arr(c)
+        .bench_vec(c);
+
+    // rand_sys: direct /dev/urandom
+    RngBencher::new(SysUrandomDirect::new().unwrap())
+        .bench_arr(c)
+        .bench_vec(c);
+
+    // rand_crate: secure os rng
+    RngBencher::new(RandCrateOs::new())
+        .bench_arr(c)
+        .bench_vec(c);
+
+    // rand_crate: secure thread rng
+    RngBencher::new(RandCrateThread::new())
         .bench_arr(c)
         .bench_vec(c);
 }
 
+/// Benchmark the generators backed by `/dev/random`, which may block waiting for entropy.
+///
+/// These are skipped when the `CI` environment variable is set, as they can stall for a long time
+/// on machines with little entropy available.
 fn bench_slow_rands(c: &mut Criterion) {
+    if env::var_os("CI").is_some() {
+        eprintln!("CI is set, skipping benchmarks of blocking /dev/random generators");
+        return;
+    }
+
     // buffered /dev/random
-    RngBencher::new(DevRandomBufRng::new(), DevRandomBufRng::PREFIX)
+    RngBencher::new(DevRandomBufRng::new())
         .bench_arr(c)
         .bench_vec(c);
 
     // direct /dev/random
-    RngBencher::new(DevRandomDirectRng::new(), DevRandomDirectRng::PREFIX)
+    RngBencher::new(DevRandomDirectRng::new())
+        .bench_arr(c)
+        .bench_vec(c);
+
+    // rand_sys: buffered /dev/random
+    RngBencher::new(SysRandomBuffered::new().unwrap())
+        .bench_arr(c)
+        .bench_vec(c);
+
+    // rand_sys: direct /dev/random
+    RngBencher::new(SysRandomDirect::new().unwrap())
         .bench_arr(c)
         .bench_vec(c);
 }
 
 criterion_group! {
-    name = rand;
+    name = rand_fast;
     config = Criterion::default();
     targets = bench_fast_rands
 }
 
-criterion_main!(rand);
+criterion_group! {
+    name = rand_slow;
+    config = Criterion::default();
+    targets = bench_slow_rands
+}
+
+criterion_main!(rand_fast, rand_slow);
@@ -0,0 +1,29 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use example_crypto::rng::{
+    OpenSslRng, ParallelRandGenerator, RandCrateOs, RandGeneratorBenchmark, RandOsRng,
+};
+
+/// Large enough that the buffer is spread across every thread in the pool.
+const BUFFER_SIZE: usize = 4 * 1024 * 1024;
+
+/// Compare a single-threaded fill of a large buffer against a parallel one.
+fn bench_rng<R: ParallelRandGenerator + RandGeneratorBenchmark>(c: &mut Criterion, mut rng: R) {
+    let mut group = c.benchmark_group(format!("{}::fill::{}", rng.prefix(), BUFFER_SIZE));
+    group.throughput(Throughput::Bytes(BUFFER_SIZE as u64));
+
+    let mut buf = vec![0; BUFFER_SIZE];
+
+    group.bench_function("serial", |b| b.iter(|| rng.fill(&mut buf)));
+    group.bench_function("parallel", |b| b.iter(|| rng.fill_parallel(&mut buf)));
+
+    group.finish();
+}
+
+fn bench_parallel_rands(c: &mut Criterion) {
+    bench_rng(c, OpenSslRng::new());
+    bench_rng(c, RandOsRng::new());
+    bench_rng(c, RandCrateOs::new());
+}
+
+criterion_group!(rand_parallel, bench_parallel_rands);
+criterion_main!(rand_parallel);
@@ -0,0 +1,85 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use example_crypto::openssl::sign::{DigitalSigner, EcdsaSigner, EllipticCurve, SignVerifyKey};
+use openssl::hash::MessageDigest;
+use rand::{thread_rng, RngCore};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const KEY_COUNT: usize = 256;
+const DATA_COUNT: usize = 512;
+
+fn signers(digest: MessageDigest) -> Vec<EcdsaSigner> {
+    (0..KEY_COUNT)
+        .map(|_| EcdsaSigner::new(SignVerifyKey::generate(EllipticCurve::SECP384R1), digest))
+        .collect()
+}
+
+fn data() -> Vec<[u8; 32]> {
+    (0..DATA_COUNT)
+        .map(|_| {
+            let mut d = [0; 32];
+            thread_rng().fill_bytes(&mut d);
+            d
+        })
+        .collect()
+}
+
+fn bench_keygen_secp384r1(c: &mut Criterion) {
+    c.bench_function("openssl::keygen::secp384r1", |b| {
+        b.iter(|| SignVerifyKey::generate(EllipticCurve::SECP384R1))
+    });
+}
+
+fn bench_sign_secp384r1(c: &mut Criterion) {
+    for (name, digest) in [
+        ("openssl::sign::secp384r1_sha256", MessageDigest::sha256()),
+        ("openssl::sign::secp384r1_sha384", MessageDigest::sha384()),
+    ] {
+        c.bench_function(name, |b| {
+            let (keys, data) = (signers(digest), data());
+            let (signer_index, data_index) = (AtomicUsize::new(0), AtomicUsize::new(0));
+
+            b.iter(|| {
+                let (current_signer, current_data) = (
+                    signer_index.fetch_add(1, Ordering::AcqRel) % KEY_COUNT,
+                    data_index.fetch_add(1, Ordering::AcqRel) % DATA_COUNT,
+                );
+
+                keys[current_signer].sign(&data[current_data])
+            })
+        });
+    }
+}
+
+fn bench_verify_secp384r1(c: &mut Criterion) {
+    for (name, digest) in [
+        ("openssl::verify::secp384r1_sha256", MessageDigest::sha256()),
+        ("openssl::verify::secp384r1_sha384", MessageDigest::sha384()),
+    ] {
+        c.bench_function(name, |b| {
+            let (keys, data) = (signers(digest), data());
+
+            // sign everything up front so that only verification is measured
+            let signatures: Vec<Vec<u8>> = keys
+                .iter()
+                .zip(data.iter().cycle())
+                .map(|(key, data)| key.sign(data))
+                .collect();
+
+            let index = AtomicUsize::new(0);
+
+            b.iter(|| {
+                let current = index.fetch_add(1, Ordering::AcqRel) % KEY_COUNT;
+
+                assert!(keys[current].verify(&data[current], &signatures[current]));
+            })
+        });
+    }
+}
+
+criterion_group! {
+    name = secp384r1;
+    config = Criterion::default();
+    targets = bench_keygen_secp384r1, bench_sign_secp384r1, bench_verify_secp384r1
+}
+
+criterion_main!(secp384r1);
@@ -1,80 +1,134 @@
-use criterion::{criterion_group, criterion_main, Criterion};
-use example_crypto::openssl::sign::Ed25519Signer;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use example_crypto::iters::EndlessShuffledIter;
+use example_crypto::openssl::sign::{DigitalSigner, EdDSASigner, EllipticCurve};
 use rand::{thread_rng, RngCore};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::fmt;
 
-fn bench_sign_ed25519(c: &mut Criterion) {
-    // eddsa
-    c.bench_function("openssl::sign::ed25519", |b| {
-        const KEY_COUNT: usize = 256;
-        const DATA_COUNT: usize = 512;
+/// How many keys, each with its own data and signature, to cycle through.
+const KEY_COUNT: usize = 256;
 
-        // build 256 signers
-        let keys: Vec<Ed25519Signer> = (0..KEY_COUNT).map(|_| Ed25519Signer::random()).collect();
+/// How many times each key appears in a shuffled cycle.
+const SHUFFLE_FACTOR: usize = 4;
 
-        let data: Vec<[u8; 32]> = (0..DATA_COUNT)
-            .map(|_| {
-                let mut d = [0; 32];
-                thread_rng().fill_bytes(&mut d);
-                d
-            })
-            .collect();
+/// The sizes of data to sign and verify, in bytes.
+const DATA_SIZES: [usize; 3] = [32, 1024, 16384];
 
-        let (signer_index, data_index) = (AtomicUsize::new(0), AtomicUsize::new(0));
-
-        b.iter(|| {
-            let (current_signer, current_data) = (
-                signer_index.fetch_add(1, Ordering::AcqRel) % KEY_COUNT,
-                data_index.fetch_add(1, Ordering::AcqRel) % DATA_COUNT,
-            );
-
-            // get em fast
-            let _sig = unsafe {
-                keys.get_unchecked(current_signer)
-                    .sign(data.get_unchecked(current_data))
-            };
-        })
-    });
+#[derive(Debug, Clone, Copy)]
+enum BenchAlgo {
+    Ed25519,
+    Ed448,
 }
 
-fn bench_sign_ed448(c: &mut Criterion) {
-    // eddsa
-    c.bench_function("openssl::sign::ed448", |b| {
-        const KEY_COUNT: usize = 256;
-        const DATA_COUNT: usize = 512;
+impl BenchAlgo {
+    const ALL: [Self; 2] = [Self::Ed25519, Self::Ed448];
+
+    fn curve(&self) -> EllipticCurve {
+        match self {
+            Self::Ed25519 => EllipticCurve::Ed25519,
+            Self::Ed448 => EllipticCurve::Ed448,
+        }
+    }
+}
 
-        // build 256 signers
-        let keys: Vec<Ed25519Signer> = (0..KEY_COUNT).map(|_| Ed25519Signer::random()).collect();
+impl fmt::Display for BenchAlgo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ed25519 => write!(f, "ed25519"),
+            Self::Ed448 => write!(f, "ed448"),
+        }
+    }
+}
 
-        let data: Vec<[u8; 32]> = (0..DATA_COUNT)
+/// Keys with random data of a given size to sign, along with signatures over that data to verify.
+struct SignBencherData {
+    signers: Vec<EdDSASigner>,
+    data: Vec<Vec<u8>>,
+    signatures: Vec<Vec<u8>>,
+}
+
+impl SignBencherData {
+    fn new(algo: BenchAlgo, size: usize) -> Self {
+        let signers: Vec<EdDSASigner> = (0..KEY_COUNT)
+            .map(|_| EdDSASigner::generate(algo.curve()))
+            .collect();
+
+        let data: Vec<Vec<u8>> = (0..KEY_COUNT)
             .map(|_| {
-                let mut d = [0; 32];
+                let mut d = vec![0; size];
                 thread_rng().fill_bytes(&mut d);
                 d
             })
             .collect();
 
-        let (signer_index, data_index) = (AtomicUsize::new(0), AtomicUsize::new(0));
-
-        b.iter(|| {
-            let (current_signer, current_data) = (
-                signer_index.fetch_add(1, Ordering::AcqRel) % KEY_COUNT,
-                data_index.fetch_add(1, Ordering::AcqRel) % DATA_COUNT,
-            );
-
-            // get em fast
-            let _sig = unsafe {
-                keys.get_unchecked(current_signer)
-                    .sign(data.get_unchecked(current_data))
-            };
-        })
-    });
+        let signatures = signers
+            .iter()
+            .zip(data.iter())
+            .map(|(signer, data)| signer.sign(data))
+            .collect();
+
+        Self {
+            signers,
+            data,
+            signatures,
+        }
+    }
+
+    /// Visit the keys in a random order so that access patterns can't be predicted.
+    fn indices(&self) -> EndlessShuffledIter<usize> {
+        EndlessShuffledIter::new((0..self.signers.len()).collect(), SHUFFLE_FACTOR)
+    }
+}
+
+fn bench_sign(c: &mut Criterion) {
+    for algo in BenchAlgo::ALL {
+        let mut group = c.benchmark_group(format!("openssl::sign::{algo}"));
+
+        for size in DATA_SIZES {
+            let bencher_data = SignBencherData::new(algo, size);
+
+            group.throughput(Throughput::Bytes(size as u64));
+            group.bench_with_input(BenchmarkId::from_parameter(size), &bencher_data, |b, d| {
+                let mut indices = d.indices();
+
+                b.iter(|| {
+                    let i = indices.next().unwrap();
+
+                    d.signers[i].sign(&d.data[i])
+                })
+            });
+        }
+
+        group.finish();
+    }
+}
+
+fn bench_verify(c: &mut Criterion) {
+    for algo in BenchAlgo::ALL {
+        let mut group = c.benchmark_group(format!("openssl::verify::{algo}"));
+
+        for size in DATA_SIZES {
+            let bencher_data = SignBencherData::new(algo, size);
+
+            group.throughput(Throughput::Bytes(size as u64));
+            group.bench_with_input(BenchmarkId::from_parameter(size), &bencher_data, |b, d| {
+                let mut indices = d.indices();
+
+                b.iter(|| {
+                    let i = indices.next().unwrap();
+
+                    assert!(d.signers[i].verify(&d.data[i], &d.signatures[i]));
+                })
+            });
+        }
+
+        group.finish();
+    }
 }
 
 criterion_group! {
     name = sign;
     config = Criterion::default();
-    targets = bench_sign_ed25519,
+    targets = bench_sign, bench_verify
 }
 
 criterion_main!(sign);
@@ -3,12 +3,12 @@ use std::io::Read;
 use std::path::PathBuf;
 use std::process::{exit, Command, Stdio};
 
-use example_crypto::openssl::client_ca::{ClientCAConfig, ClientCAExample};
+use example_crypto::openssl::client_ca::{
+    describe_cert, load_pkcs12, split_pem_chain, ClientCAConfig, ClientCAExample,
+};
 
 use base64::prelude::BASE64_URL_SAFE_NO_PAD;
 use base64::Engine as _;
-use openssl::pkcs12::Pkcs12;
-use openssl::stack::Stack;
 use rand::{thread_rng, RngCore};
 
 #[cfg(not(windows))]
@@ -48,11 +48,28 @@ fn main() {
     let ca = ClientCAExample::generate(ClientCAConfig::default()).unwrap();
 
     eprintln!("Validating full CA chain...");
-    if !ca.verify().unwrap() {
-        eprintln!("Verification failed");
+    if let Err(e) = ca.verify() {
+        eprintln!("Verification failed: {e}");
         exit(1);
     }
 
+    eprintln!(
+        "Root CA fingerprint (SHA-256): {}",
+        ca.root_fingerprint_sha256().unwrap()
+    );
+    eprintln!(
+        "Intermediate CA fingerprint (SHA-256): {}",
+        ca.intermediate_fingerprint_sha256().unwrap()
+    );
+    eprintln!(
+        "Client fingerprint (SHA-256): {}",
+        ca.client_fingerprint_sha256().unwrap()
+    );
+    eprintln!(
+        "Client certificate:\n{}",
+        describe_cert(&ca.client_cert).unwrap()
+    );
+
     // write root key
     fs::write(
         output_dir.join("root-ca.key.pem"),
@@ -67,17 +84,20 @@ fn main() {
     )
     .unwrap();
 
+    // the default config has a single intermediate layer
+    let (intermediate_key, intermediate_cert) = ca.issuer();
+
     // write intermediate key
     fs::write(
         output_dir.join("intermediate-ca.key.pem"),
-        ca.intermediate_key.private_key_to_pem_pkcs8().unwrap(),
+        intermediate_key.private_key_to_pem_pkcs8().unwrap(),
     )
     .unwrap();
 
     // write intermediate cert
     fs::write(
         output_dir.join("intermediate-ca.crt.pem"),
-        ca.intermediate_cert.to_pem().unwrap(),
+        intermediate_cert.to_pem().unwrap(),
     )
     .unwrap();
 
@@ -86,7 +106,7 @@ fn main() {
         // root cert, then newline, then intermediate cert
         let mut v = ca.root_cert.to_pem().unwrap();
         v.extend_from_slice(LINE_ENDING.as_bytes());
-        v.extend(ca.intermediate_cert.to_pem().unwrap());
+        v.extend(intermediate_cert.to_pem().unwrap());
         v
     };
 
@@ -111,13 +131,23 @@ fn main() {
     .unwrap();
 
     // write client cert chain
-    let client_chain = {
-        let mut v = ca.root_cert.to_pem().unwrap();
-        v.extend_from_slice(LINE_ENDING.as_bytes());
-        v.extend(ca.intermediate_cert.to_pem().unwrap());
-        v.extend(ca.client_cert.to_pem().unwrap());
-        v
-    };
+    let client_chain = ca.to_pem_chain().unwrap();
+
+    // read the chain back to make sure it round-trips: root, intermediate, then client
+    match split_pem_chain(&client_chain) {
+        Ok(certs) if certs.len() == 3 => {}
+        Ok(certs) => {
+            eprintln!(
+                "Expected 3 certificates in client chain, found {}",
+                certs.len()
+            );
+            exit(1);
+        }
+        Err(e) => {
+            eprintln!("Unable to read back client chain: {e}");
+            exit(1);
+        }
+    }
 
     fs::write(output_dir.join("client-chain.crt.pem"), client_chain).unwrap();
 
@@ -130,23 +160,13 @@ fn main() {
 
     println!("Generated password for client key PKCS12 bundle: {key}");
 
-    let pkcs12 = {
-        let mut p = Pkcs12::builder();
-        p.pkey(&ca.client_key);
-        p.cert(&ca.client_cert);
-
-        let mut cert_stack = Stack::new().unwrap();
-        cert_stack.push(ca.root_cert).unwrap();
-        cert_stack.push(ca.intermediate_cert).unwrap();
+    let bundle = ca.to_pkcs12(key.as_str()).unwrap();
 
-        p.ca(cert_stack);
-
-        p.build2(key.as_str()).unwrap()
-    };
+    // read the bundle back to make sure it round-trips
+    if let Err(e) = load_pkcs12(&bundle, key.as_str()) {
+        eprintln!("Unable to read back PKCS12 bundle: {e}");
+        exit(1);
+    }
 
-    fs::write(
-        output_dir.join("client-bundle.p12"),
-        pkcs12.to_der().unwrap(),
-    )
-    .unwrap();
+    fs::write(output_dir.join("client-bundle.p12"), bundle).unwrap();
 }
@@ -0,0 +1,70 @@
+//! Iterators which never run out, for feeding inputs to benchmarks without branching on
+//! exhaustion.
+
+#[cfg(test)]
+mod tests;
+
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+
+/// Cycles over its values in order, forever.
+///
+/// Yields nothing at all if it has no values.
+pub struct EndlessIter<T> {
+    values: Vec<T>,
+    current: usize,
+}
+
+impl<T> EndlessIter<T> {
+    pub fn new(values: Vec<T>) -> Self {
+        Self { values, current: 0 }
+    }
+}
+
+impl<T: Clone> Iterator for EndlessIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.values.get(self.current).cloned()?;
+
+        self.current = (self.current + 1) % self.values.len();
+
+        Some(value)
+    }
+}
+
+/// Cycles over its data in a random order, forever.
+///
+/// Each element appears `factor` times in the shuffled order, which is repeated once exhausted.
+/// Yields nothing at all if it has no data or `factor` is zero.
+pub struct EndlessShuffledIter<T> {
+    data: Vec<T>,
+    indices: Vec<usize>,
+    current: usize,
+}
+
+impl<T> EndlessShuffledIter<T> {
+    pub fn new(data: Vec<T>, factor: usize) -> Self {
+        let mut indices: Vec<usize> = (0..factor).flat_map(|_| 0..data.len()).collect();
+
+        indices.shuffle(&mut thread_rng());
+
+        Self {
+            data,
+            indices,
+            current: 0,
+        }
+    }
+}
+
+impl<T: Clone> Iterator for EndlessShuffledIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.data.get(*self.indices.get(self.current)?).cloned();
+
+        self.current = (self.current + 1) % self.indices.len();
+
+        value
+    }
+}
@@ -0,0 +1,52 @@
+use super::{EndlessIter, EndlessShuffledIter};
+
+#[test]
+fn test_endless_iter_wraps_around() {
+    let values: Vec<u8> = EndlessIter::new(vec![1, 2, 3]).take(8).collect();
+
+    assert_eq!(vec![1, 2, 3, 1, 2, 3, 1, 2], values);
+}
+
+#[test]
+fn test_endless_iter_empty() {
+    assert_eq!(None, EndlessIter::<u8>::new(Vec::new()).next());
+}
+
+#[test]
+fn test_endless_shuffled_iter_is_endless() {
+    let mut iter = EndlessShuffledIter::new(vec![1, 2, 3], 2);
+
+    assert_eq!(18, iter.by_ref().take(18).count());
+    assert_eq!(None, EndlessShuffledIter::<u8>::new(Vec::new(), 2).next());
+}
+
+#[test]
+fn test_endless_shuffled_iter_full_cycle() {
+    const LEN: usize = 64;
+    const FACTOR: usize = 3;
+
+    let data: Vec<usize> = (0..LEN).collect();
+    let mut iter = EndlessShuffledIter::new(data.clone(), FACTOR);
+
+    // elements are drawn through the shuffled indices rather than walking the data in order
+    let expected: Vec<usize> = iter.indices.iter().map(|&i| data[i]).collect();
+    let cycle: Vec<usize> = iter.by_ref().take(LEN * FACTOR).collect();
+
+    assert_eq!(expected, cycle);
+    assert_ne!(
+        data.repeat(FACTOR),
+        cycle,
+        "a full cycle should not be in sequential order"
+    );
+
+    let mut visits = [0; LEN];
+
+    for value in cycle.iter() {
+        visits[*value] += 1;
+    }
+
+    assert!(visits.iter().all(|&v| v == FACTOR), "{visits:?}");
+
+    // the next cycle repeats the same shuffled order
+    assert_eq!(expected, iter.take(LEN * FACTOR).collect::<Vec<_>>());
+}
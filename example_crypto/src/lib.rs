@@ -1,2 +1,3 @@
+pub mod iters;
 pub mod openssl;
 pub mod rng;
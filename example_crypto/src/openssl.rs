@@ -1,4 +1,5 @@
 pub mod asymm;
 pub mod client_ca;
 pub mod keygen;
+pub mod self_signed;
 pub mod sign;
@@ -1,17 +1,37 @@
-//! Example demonstrating the generation of a two-layer certificate authority (CA) and a client
+//! Example demonstrating the generation of a multi-layer certificate authority (CA) and a client
 //! certificate, verifying the whole process.
 
+#[cfg(test)]
+mod tests;
+
 use openssl::asn1::{Asn1Integer, Asn1Time};
 use openssl::error::ErrorStack;
 use openssl::hash::MessageDigest;
 use openssl::nid::Nid;
-use openssl::pkey::{PKey, Private};
-use openssl::x509::extension::{BasicConstraints, ExtendedKeyUsage, KeyUsage};
-use openssl::x509::{X509Name, X509NameRef, X509VerifyResult, X509};
+use openssl::pkcs12::Pkcs12;
+use openssl::pkey::{Id, PKey, Private};
+use openssl::rsa::Rsa;
+use openssl::stack::{Stack, StackRef};
+use openssl::x509::extension::{
+    AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
+    SubjectKeyIdentifier,
+};
+use openssl::x509::store::{X509StoreBuilder, X509StoreRef};
+use openssl::x509::verify::X509VerifyParam;
+use openssl::x509::{
+    CrlNumber, X509Builder, X509Crl, X509CrlBuilder, X509Extension, X509Name, X509NameRef, X509Ref,
+    X509RevokedBuilder, X509StoreContext, X509VerifyResult, X509,
+};
 
-use openssl::bn::BigNum;
+use openssl::bn::{BigNum, MsbOption};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::fmt::{Display, Formatter};
+use std::net::IpAddr;
 use std::ops::{Add, Sub};
+use std::thread;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
 
 /// The amount of time before now to allow this certificate to be used for.
 ///
@@ -24,6 +44,11 @@ pub const NOT_BEFORE_DRIFT_DURATION: Duration = Duration::from_secs(60 * 30);
 /// Set to 1 hour.
 pub const CLIENT_EXPIRY_DURATION: Duration = Duration::from_secs(60 * 60);
 
+/// The certificate expiry duration for the server certificate.
+///
+/// Set to 1 hour.
+pub const SERVER_EXPIRY_DURATION: Duration = Duration::from_secs(60 * 60);
+
 /// The certificate expiry duration for the intermediate CA.
 ///
 /// Set to 3 hours.
@@ -34,181 +59,627 @@ pub const INTERMEDIATE_EXPIRY_DURATION: Duration = Duration::from_secs(60 * 60 *
 /// Set to 6 hours.
 pub const ROOT_EXPIRY_DURATION: Duration = Duration::from_secs(60 * 60 * 6);
 
+/// How long a generated certificate revocation list is valid before a newer one is expected.
+///
+/// Set to 1 hour.
+pub const CRL_EXPIRY_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// The public exponent used for RSA keys unless otherwise configured, F4.
+pub const RSA_EXPONENT: u32 = 65537;
+
 /// The X509 version to use when generating certificates.
 ///
 /// This is zero-indexed, so version 3 is represented by `2`.
 pub const X509_CERT_VERSION: i32 = 3 - 1;
 
+/// The size in bits of randomly generated certificate serial numbers.
+///
+/// RFC 5280 permits serials of up to 20 octets; 128 bits leaves plenty of headroom while making
+/// collisions practically impossible.
+pub const SERIAL_NUMBER_BITS: i32 = 128;
+
 /// The subject name to use for the client certificate.
 pub const X509_CLIENT_SUBJECT_NAME: &str = "Rust Example Client Certificate";
 
+/// The subject name to use for the server certificate.
+pub const X509_SERVER_SUBJECT_NAME: &str = "Rust Example Server Certificate";
+
 /// The subject name to use for the intermediate certificate.
 pub const X509_INTERMEDIATE_SUBJECT_NAME: &str = "Rust Example Intermediate CA";
 
 /// The subject name to use for the root certificate.
 pub const X509_ROOT_SUBJECT_NAME: &str = "Rust Example Root CA";
 
-/// An example which generates a root CA, intermediate CA, and a client certificate.
+/// The country name to use for the CA certificates.
+pub const X509_COUNTRY_NAME: &str = "US";
+
+/// The organization name to use for the CA certificates.
+pub const X509_ORGANIZATION_NAME: &str = "Naftuli, Inc.";
+
+/// The organizational unit name to use for the CA certificates.
+pub const X509_ORGANIZATIONAL_UNIT_NAME: &str = "naftuli.wtf";
+
+/// Errors which can occur while generating or verifying the CA.
+#[derive(Debug, Error)]
+pub enum CaError {
+    /// A private key could not be generated.
+    #[error("unable to generate key")]
+    KeyGeneration(#[source] ErrorStack),
+    /// A certificate extension could not be built.
+    #[error("unable to build certificate extension")]
+    Extension(#[source] ErrorStack),
+    /// A certificate could not be signed by its issuer.
+    #[error("unable to sign certificate")]
+    Signing(#[source] ErrorStack),
+    /// Any other failure while assembling a certificate.
+    #[error("unable to build certificate")]
+    Certificate(#[from] ErrorStack),
+    /// A certificate in the chain was not issued by its purported issuer.
+    #[error("verification of {link} certificate failed: {reason}")]
+    Verification { link: ChainLink, reason: String },
+    /// A PEM-encoded key or certificate could not be parsed.
+    #[error("unable to parse {link} {part} PEM")]
+    Pem {
+        link: ChainLink,
+        /// Which part of the link failed to parse, either the private key or the certificate.
+        part: &'static str,
+        #[source]
+        source: ErrorStack,
+    },
+    /// A private key does not belong to the certificate it was loaded alongside.
+    #[error("{0} private key does not match its certificate")]
+    KeyMismatch(ChainLink),
+    /// A PKCS#12 bundle could not be parsed or decrypted, e.g. due to a wrong password.
+    #[error("unable to read PKCS#12 bundle")]
+    Pkcs12(#[source] ErrorStack),
+    /// A PKCS#12 bundle is missing a part of the identity.
+    #[error("PKCS#12 bundle has no {0}")]
+    Pkcs12Missing(&'static str),
+    /// Keys of this algorithm can't be derived deterministically from a seed.
+    #[error("{0:?} keys can't be derived from a seed")]
+    Unseedable(KeyAlgorithm),
+    /// A certificate could not be verified against a CA bundle, e.g. because it was issued by
+    /// an unrelated CA.
+    #[error("certificate is not trusted by the CA bundle at depth {depth}")]
+    Untrusted {
+        /// The depth in the chain at which verification failed, the leaf being at depth zero.
+        depth: u32,
+        #[source]
+        reason: X509VerifyResult,
+    },
+}
+
+/// A client identity read back from a PKCS#12 bundle, such as one produced by
+/// [ClientCAExample::to_pkcs12].
+pub struct ParsedIdentity {
+    pub key: PKey<Private>,
+    pub cert: X509,
+    /// The CA certificates bundled alongside the client certificate.
+    pub chain: Vec<X509>,
+}
+
+/// Parse and decrypt a DER-encoded PKCS#12 bundle, verifying that its private key belongs to its
+/// certificate.
+pub fn load_pkcs12(der: &[u8], password: &str) -> Result<ParsedIdentity, CaError> {
+    let parsed = Pkcs12::from_der(der)
+        .and_then(|p| p.parse2(password))
+        .map_err(CaError::Pkcs12)?;
+
+    let key = parsed.pkey.ok_or(CaError::Pkcs12Missing("private key"))?;
+    let cert = parsed.cert.ok_or(CaError::Pkcs12Missing("certificate"))?;
+
+    if !cert.public_key()?.public_eq(&key) {
+        return Err(CaError::KeyMismatch(ChainLink::Client));
+    }
+
+    Ok(ParsedIdentity {
+        key,
+        cert,
+        chain: parsed
+            .ca
+            .map(|ca| ca.into_iter().collect())
+            .unwrap_or_default(),
+    })
+}
+
+/// Split a bundle of concatenated PEM certificates, such as one written by
+/// [ClientCAExample::to_pem_chain], into individual certificates in the order they appear.
+///
+/// OpenSSL's PEM reader accepts both `\n` and `\r\n` line endings, so bundles written with the
+/// `LINE_ENDING` of any platform can be read back anywhere.
+pub fn split_pem_chain(data: &[u8]) -> Result<Vec<X509>, ErrorStack> {
+    X509::stack_from_pem(data)
+}
+
+/// Whether a certificate's window of validity covers now, allowing for clocks which are up to
+/// `skew` apart in either direction, in the same vein as [NOT_BEFORE_DRIFT_DURATION].
+///
+/// A certificate is considered valid if its `notBefore` is no later than `skew` from now, and its
+/// `notAfter` is no earlier than `skew` ago. Only the dates are checked, not the signature.
+pub fn is_currently_valid(cert: &X509, skew: Duration) -> bool {
+    let now = SystemTime::now();
+
+    let (Ok(earliest), Ok(latest)) = (
+        ClientCAExample::asn1_time(now.sub(skew)),
+        ClientCAExample::asn1_time(now.add(skew)),
+    ) else {
+        return false;
+    };
+
+    cert.not_before() <= latest && cert.not_after() >= earliest
+}
+
+/// Verify a leaf certificate against a bundle of CA certificates, such as a root and its
+/// intermediates, the way a TLS stack would.
+///
+/// Every certificate in the bundle is trusted, so the bundle should only contain certificates
+/// which are themselves known to be good. Returns a [CaError::Untrusted] carrying the store
+/// context's error if the leaf isn't trusted.
+pub fn verify_against_ca(leaf: &X509, ca_chain: &[X509]) -> Result<(), CaError> {
+    let store = {
+        let mut builder = X509StoreBuilder::new()?;
+
+        for cert in ca_chain {
+            builder.add_cert(cert.clone())?;
+        }
+
+        builder.build()
+    };
+
+    // every certificate is trusted, so there are no untrusted intermediates
+    let chain = Stack::new()?;
+
+    verify_in_store(&store, leaf, &chain)
+}
+
+/// Verify a leaf certificate against the trusted certificates in `store`, using `chain` as
+/// untrusted intermediates, returning a [CaError::Untrusted] if verification fails.
+fn verify_in_store(
+    store: &X509StoreRef,
+    leaf: &X509Ref,
+    chain: &StackRef<X509>,
+) -> Result<(), CaError> {
+    let mut context = X509StoreContext::new()?;
+
+    let failure = context.init(store, leaf, chain, |c| {
+        Ok((!c.verify_cert()?).then(|| (c.error_depth(), c.error())))
+    })?;
+
+    match failure {
+        None => Ok(()),
+        Some((depth, reason)) => Err(CaError::Untrusted { depth, reason }),
+    }
+}
+
+/// Describe a certificate in a trimmed-down, human-readable form akin to `openssl x509 -text`,
+/// listing its subject, issuer, serial number, validity window, and key algorithm.
+pub fn describe_cert(cert: &X509Ref) -> Result<String, ErrorStack> {
+    let key = cert.public_key()?;
+    let key_algo = Nid::from_raw(key.id().as_raw())
+        .long_name()
+        .unwrap_or("unknown");
+
+    Ok(format!(
+        "Subject: {}\n\
+         Issuer: {}\n\
+         Serial: {}\n\
+         Not Before: {}\n\
+         Not After: {}\n\
+         Public Key: {} ({} bits)\n\
+         Signature Algorithm: {}\n",
+        describe_name(cert.subject_name()),
+        describe_name(cert.issuer_name()),
+        cert.serial_number().to_bn()?.to_hex_str()?,
+        cert.not_before(),
+        cert.not_after(),
+        key_algo,
+        key.bits(),
+        cert.signature_algorithm()
+            .object()
+            .nid()
+            .long_name()
+            .unwrap_or("unknown"),
+    ))
+}
+
+/// Format a distinguished name as comma-separated `KEY=value` pairs, e.g. `CN=Client, O=Org`.
+fn describe_name(name: &X509NameRef) -> String {
+    name.entries()
+        .map(|entry| {
+            let key = entry.object().nid().short_name().unwrap_or("?");
+            let value = String::from_utf8_lossy(entry.data().as_slice());
+
+            format!("{key}={value}")
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A link in the certificate chain, identifying the certificate being verified.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ChainLink {
+    Root,
+    /// An intermediate CA, indexed from the one signed by the root.
+    Intermediate(usize),
+    Client,
+    Server,
+}
+
+impl Display for ChainLink {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Root => f.write_str("root"),
+            Self::Intermediate(index) => write!(f, "intermediate #{index}"),
+            Self::Client => f.write_str("client"),
+            Self::Server => f.write_str("server"),
+        }
+    }
+}
+
+/// The revocation status of a certificate, in the manner of an OCSP response.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CertStatus {
+    /// The certificate was issued by this CA and has not been revoked.
+    Good,
+    /// The certificate was issued by this CA and has been revoked.
+    Revoked,
+    /// The certificate was not issued by this CA, so nothing is known about it.
+    Unknown,
+}
+
+/// An example which generates a root CA, a chain of intermediate CAs, and a client certificate.
 pub struct ClientCAExample {
     /// The root CA's public/private keypair.
     pub root_key: PKey<Private>,
     /// The root CA's self-signed certificate.
     pub root_cert: X509,
-    /// The intermediate CA's public/private keypair.
-    pub intermediate_key: PKey<Private>,
-    /// The intermediate CA's certificate, signed by the root CA cert.
-    pub intermediate_cert: X509,
+    /// The intermediate CAs' keypairs and certificates, ordered from the one signed by the root CA
+    /// down to the one which issues leaf certificates.
+    pub intermediates: Vec<(PKey<Private>, X509)>,
     /// The client certificate's public/private keypair.
     pub client_key: PKey<Private>,
-    /// The client certificate, signed by the intermediate CA cert.
+    /// The client certificate, signed by the last intermediate CA cert.
     pub client_cert: X509,
 }
 
 impl ClientCAExample {
-    /// Generate a two-layer TLS client CA with a root, intermediate, and single client certificate
-    /// and private keys.
+    /// Generate a TLS client CA with a root, one or more intermediates, and a single client
+    /// certificate and private keys.
     ///
-    /// Use [Default::default] to generate the CA config if you'd like to accept default settings.
-    pub fn generate(config: ClientCAConfig) -> Result<Self, ErrorStack> {
-        let root_key = Self::generate_key()?;
-        let root_cert = Self::generate_root_cert(&config.root_config, &root_key)?;
-
-        let intermediate_key = Self::generate_key()?;
-        let intermediate_cert = Self::generate_intermediate_cert(
-            &config.intermediate_config,
-            &intermediate_key,
-            &root_key,
-            root_cert.subject_name(),
-        )?;
+    /// Each intermediate is signed by the one before it, and basic constraint path lengths are
+    /// derived from the number of intermediate layers configured. Use [Default::default] to
+    /// generate the CA config if you'd like to accept default settings.
+    ///
+    /// The private keys are generated concurrently, one thread per key, which makes a noticeable
+    /// difference for heavier algorithms such as RSA. Signing then proceeds in order down the
+    /// chain.
+    pub fn generate(config: ClientCAConfig) -> Result<Self, CaError> {
+        let keys = thread::scope(|scope| {
+            let handles: Vec<_> = Self::key_algos(&config)
+                .map(|algo| scope.spawn(move || algo.generate().map_err(CaError::KeyGeneration)))
+                .collect();
 
-        let client_key = Self::generate_key()?;
-        let client_cert = Self::generate_client_cert(
-            &config.client_config,
-            &client_key,
-            &intermediate_key,
-            intermediate_cert.subject_name(),
-        )?;
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("key generation thread panicked"))
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+
+        Self::generate_with(config, keys)
+    }
+
+    /// Generate a TLS client CA as in [ClientCAExample::generate], but generating the private keys
+    /// one after another on the calling thread.
+    ///
+    /// This is mostly useful as a baseline to compare the concurrent key generation against.
+    pub fn generate_sequential(config: ClientCAConfig) -> Result<Self, CaError> {
+        let keys = Self::key_algos(&config)
+            .map(|algo| algo.generate().map_err(CaError::KeyGeneration))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::generate_with(config, keys)
+    }
+
+    /// Generate a TLS client CA as in [ClientCAExample::generate], deriving all key material and
+    /// any unset serial numbers deterministically from `seed`.
+    ///
+    /// Given the same seed and a config with pinned validity windows, the resulting certificates
+    /// are byte-for-byte identical, which is useful for golden tests. Only EdDSA keys, whose
+    /// signatures are also deterministic, can be derived from a seed; other algorithms fail with
+    /// [CaError::Unseedable]. Seeded serial numbers are 32 bits wide rather than
+    /// [SERIAL_NUMBER_BITS].
+    ///
+    /// The seed is expanded into the ChaCha20 keystream, which is stable across platforms and
+    /// releases of [rand_chacha].
+    pub fn generate_seeded(mut config: ClientCAConfig, seed: [u8; 32]) -> Result<Self, CaError> {
+        let mut rng = ChaCha20Rng::from_seed(seed);
+
+        // assign serials up front, so they don't depend on the order of key generation
+        for serial in std::iter::once(&mut config.root_config.serial_number)
+            .chain(
+                config
+                    .intermediate_configs
+                    .iter_mut()
+                    .map(|c| &mut c.serial_number),
+            )
+            .chain(std::iter::once(&mut config.client_config.serial_number))
+        {
+            // zero isn't a valid serial number
+            serial.get_or_insert_with(|| rng.next_u32().max(1));
+        }
+
+        let keys = Self::key_algos(&config)
+            .map(|algo| Self::seeded_key(algo, &mut rng))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::generate_with(config, keys)
+    }
+
+    /// The key algorithm of each layer in the chain, from the root down to the client.
+    fn key_algos(config: &ClientCAConfig) -> impl Iterator<Item = &KeyAlgorithm> {
+        std::iter::once(&config.root_config.key_algo)
+            .chain(config.intermediate_configs.iter().map(|c| &c.key_algo))
+            .chain(std::iter::once(&config.client_config.key_algo))
+    }
+
+    /// Derive a private key of the given algorithm from raw bytes drawn from `rng`.
+    fn seeded_key(algo: &KeyAlgorithm, rng: &mut ChaCha20Rng) -> Result<PKey<Private>, CaError> {
+        let (id, len) = match algo {
+            KeyAlgorithm::Ed25519 => (Id::ED25519, 32),
+            KeyAlgorithm::Ed448 => (Id::ED448, 57),
+            _ => return Err(CaError::Unseedable(*algo)),
+        };
+
+        let mut bytes = vec![0; len];
+        rng.fill_bytes(&mut bytes);
+
+        PKey::private_key_from_raw_bytes(&bytes, id).map_err(CaError::KeyGeneration)
+    }
+
+    /// Generate the CA from pre-generated private keys, ordered as in
+    /// [ClientCAExample::key_algos].
+    fn generate_with(config: ClientCAConfig, keys: Vec<PKey<Private>>) -> Result<Self, CaError> {
+        let layers = config.intermediate_configs.len() as u32;
+
+        debug_assert_eq!(config.intermediate_configs.len() + 2, keys.len());
+        let mut keys = keys.into_iter();
+
+        let root_key = keys.next().expect("missing root key");
+        let root_cert = Self::generate_root_cert(&config.root_config, &root_key, layers)?;
+
+        let mut intermediates: Vec<(PKey<Private>, X509)> =
+            Vec::with_capacity(config.intermediate_configs.len());
+
+        for (index, intermediate_config) in config.intermediate_configs.iter().enumerate() {
+            let key = keys.next().expect("missing intermediate key");
+
+            // each intermediate is signed by the previous one, the first by the root
+            let (issuer_key, issuer_cert) = intermediates
+                .last()
+                .map(|(k, c)| (k, c))
+                .unwrap_or((&root_key, &root_cert));
+
+            let cert = Self::generate_intermediate_cert(
+                intermediate_config,
+                &key,
+                issuer_key,
+                issuer_cert,
+                layers - 1 - index as u32,
+            )?;
+
+            intermediates.push((key, cert));
+        }
+
+        let client_key = keys.next().expect("missing client key");
+
+        let client_cert = {
+            let (issuer_key, issuer_cert) = intermediates
+                .last()
+                .map(|(k, c)| (k, c))
+                .unwrap_or((&root_key, &root_cert));
+
+            Self::generate_client_cert(&config.client_config, &client_key, issuer_key, issuer_cert)?
+        };
 
         Ok(Self {
             root_key,
             root_cert,
+            intermediates,
+            client_key,
+            client_cert,
+        })
+    }
+
+    /// Load an existing CA with a single intermediate from PEM-encoded private keys and
+    /// certificates, such as those written to disk by the `client_ca` example.
+    ///
+    /// Each private key must match the public key of its certificate. The chain itself is not
+    /// checked, use [ClientCAExample::verify] for that.
+    pub fn from_pem(
+        root_key: &[u8],
+        root_cert: &[u8],
+        intermediate_key: &[u8],
+        intermediate_cert: &[u8],
+        client_key: &[u8],
+        client_cert: &[u8],
+    ) -> Result<Self, CaError> {
+        let (root_key, root_cert) = Self::load_pem(ChainLink::Root, root_key, root_cert)?;
+        let intermediate = Self::load_pem(
+            ChainLink::Intermediate(0),
             intermediate_key,
             intermediate_cert,
+        )?;
+        let (client_key, client_cert) = Self::load_pem(ChainLink::Client, client_key, client_cert)?;
+
+        Ok(Self {
+            root_key,
+            root_cert,
+            intermediates: vec![intermediate],
             client_key,
             client_cert,
         })
     }
 
-    /// Generate a private Ed25519 key.
-    fn generate_key() -> Result<PKey<Private>, ErrorStack> {
-        PKey::generate_ed25519()
+    /// Parse a PEM-encoded private key and certificate, checking that they belong together.
+    fn load_pem(
+        link: ChainLink,
+        key: &[u8],
+        cert: &[u8],
+    ) -> Result<(PKey<Private>, X509), CaError> {
+        let key = PKey::private_key_from_pem(key).map_err(|source| CaError::Pem {
+            link,
+            part: "private key",
+            source,
+        })?;
+
+        let cert = X509::from_pem(cert).map_err(|source| CaError::Pem {
+            link,
+            part: "certificate",
+            source,
+        })?;
+
+        if !cert.public_key()?.public_eq(&key) {
+            return Err(CaError::KeyMismatch(link));
+        }
+
+        Ok((key, cert))
+    }
+
+    /// The keypair and certificate of the CA which issues leaf certificates: the last intermediate,
+    /// or the root if there are no intermediates.
+    pub fn issuer(&self) -> (&PKey<Private>, &X509) {
+        self.intermediates
+            .last()
+            .map(|(k, c)| (k, c))
+            .unwrap_or((&self.root_key, &self.root_cert))
+    }
+
+    /// Select the message digest to use when signing with the given issuer key.
+    ///
+    /// EdDSA signs the message directly and must use a null digest, whereas ECDSA and RSA sign a
+    /// hash of the message.
+    pub(crate) fn signature_digest(
+        issuer_key: &PKey<Private>,
+    ) -> Result<MessageDigest, ErrorStack> {
+        Ok(match issuer_key.id() {
+            Id::ED25519 | Id::ED448 => MessageDigest::null(),
+            Id::EC if issuer_key.ec_key()?.group().degree() >= 384 => MessageDigest::sha384(),
+            _ => MessageDigest::sha256(),
+        })
     }
 
     /// Generate the root CA certificate.
-    fn generate_root_cert(config: &CAConfig, key: &PKey<Private>) -> Result<X509, ErrorStack> {
-        let subject_name = {
-            let mut n = X509Name::builder()?;
-            n.append_entry_by_nid(Nid::COMMONNAME, config.subject_name.as_str())?;
-            n.append_entry_by_nid(Nid::COUNTRYNAME, "US")?;
-            n.append_entry_by_nid(Nid::ORGANIZATIONNAME, "Naftuli, Inc.")?;
-            n.append_entry_by_nid(Nid::ORGANIZATIONALUNITNAME, "naftuli.wtf")?;
-            n.build()
-        };
+    fn generate_root_cert(
+        config: &CAConfig,
+        key: &PKey<Private>,
+        path_length: u32,
+    ) -> Result<X509, CaError> {
+        let subject_name = config.subject.to_x509_name()?;
 
         // set key usage
         let key_usage = KeyUsage::new()
             .critical()
             .digital_signature()
             .key_cert_sign()
-            .build()?;
+            .build()
+            .map_err(CaError::Extension)?;
 
-        // set basic constraints to being critical, being a CA, and only supporting one level of
-        // intermediate CA certificates
+        // set basic constraints to being critical, being a CA, and only supporting as many levels
+        // of intermediate CA certificates as were requested
         let basic = BasicConstraints::new()
             .critical()
             .ca()
-            .pathlen(config.ca_type.path_length())
-            .build()?;
+            .pathlen(path_length)
+            .build()
+            .map_err(CaError::Extension)?;
 
         // set window of validity for certificate
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-        let not_before = Asn1Time::from_unix(now.sub(config.max_drift).as_secs() as i64)?;
-        let not_after = Asn1Time::from_unix(now.add(config.validity).as_secs() as i64)?;
+        let (not_before, not_after) = Self::validity_window(
+            config.max_drift,
+            config.validity,
+            config.not_before,
+            config.not_after,
+        )?;
 
         let cert = {
             let mut builder = X509::builder()?;
             builder.set_version(X509_CERT_VERSION)?;
             builder.set_subject_name(&subject_name)?;
-            builder.set_serial_number(
-                Asn1Integer::from_bn(BigNum::from_u32(config.serial_number)?.as_ref())?.as_ref(),
-            )?;
+            builder.set_serial_number(Self::serial_number(config.serial_number)?.as_ref())?;
             // set issuer name to subject name (self-referential) on root ca
             builder.set_issuer_name(&subject_name)?;
             builder.set_not_before(&not_before)?;
             builder.set_not_after(&not_after)?;
             builder.set_pubkey(key)?;
+            // identify the key so that issued certificates can refer to it
+            builder.append_extension(Self::subject_key_id(&builder)?)?;
             builder.append_extension(key_usage)?;
             builder.append_extension(basic)?;
-            // with ed25519, it seems openssl does not support message digest for the signature
-            builder.sign(key, MessageDigest::null())?;
+            // self-signed, so the digest is determined by our own key
+            builder
+                .sign(key, Self::signature_digest(key)?)
+                .map_err(CaError::Signing)?;
             builder.build()
         };
 
         Ok(cert)
     }
 
-    /// Generate the intermediate CA certificate.
+    /// Generate an intermediate CA certificate signed by the given issuer, permitting
+    /// `path_length` further intermediate CAs beneath it.
     pub fn generate_intermediate_cert(
         config: &CAConfig,
         key: &PKey<Private>,
-        root_key: &PKey<Private>,
-        root_subject_name: &X509NameRef,
-    ) -> Result<X509, ErrorStack> {
-        let subject_name = {
-            let mut n = X509Name::builder()?;
-            n.append_entry_by_nid(Nid::COMMONNAME, config.subject_name.as_str())?;
-            n.append_entry_by_nid(Nid::COUNTRYNAME, "US")?;
-            n.append_entry_by_nid(Nid::ORGANIZATIONNAME, "Naftuli, Inc.")?;
-            n.append_entry_by_nid(Nid::ORGANIZATIONALUNITNAME, "naftuli.wtf")?;
-            n.build()
-        };
+        issuer_key: &PKey<Private>,
+        issuer_cert: &X509Ref,
+        path_length: u32,
+    ) -> Result<X509, CaError> {
+        let subject_name = config.subject.to_x509_name()?;
 
         // set key usage
         let key_usage = KeyUsage::new()
             .critical()
             .digital_signature()
             .key_cert_sign()
-            .build()?;
+            .build()
+            .map_err(CaError::Extension)?;
 
         // set basic constraints
         let basic = BasicConstraints::new()
             .critical()
             .ca()
-            .pathlen(config.ca_type.path_length())
-            .build()?;
+            .pathlen(path_length)
+            .build()
+            .map_err(CaError::Extension)?;
 
         // set window of validity
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-        let not_before = Asn1Time::from_unix(now.sub(config.max_drift).as_secs() as i64)?;
-        let not_after = Asn1Time::from_unix(now.add(config.validity).as_secs() as i64)?;
+        let (not_before, not_after) = Self::validity_window(
+            config.max_drift,
+            config.validity,
+            config.not_before,
+            config.not_after,
+        )?;
 
         let cert = {
             let mut builder = X509::builder()?;
             builder.set_version(X509_CERT_VERSION)?;
             builder.set_subject_name(&subject_name)?;
-            builder.set_serial_number(
-                Asn1Integer::from_bn(BigNum::from_u32(config.serial_number)?.as_ref())?.as_ref(),
-            )?;
+            builder.set_serial_number(Self::serial_number(config.serial_number)?.as_ref())?;
             // set issuer since we have a parent
-            builder.set_issuer_name(root_subject_name)?;
+            builder.set_issuer_name(issuer_cert.subject_name())?;
             builder.set_not_before(&not_before)?;
             builder.set_not_after(&not_after)?;
             builder.set_pubkey(key)?;
+            // identify the key, and the issuer's key by its own identifier
+            builder.append_extension(Self::subject_key_id(&builder)?)?;
+            builder.append_extension(Self::authority_key_id(&builder, issuer_cert)?)?;
             builder.append_extension(key_usage)?;
             builder.append_extension(basic)?;
 
-            // sign using the root ca key
-            builder.sign(root_key, MessageDigest::null())?;
+            // sign using the issuing ca key
+            builder
+                .sign(issuer_key, Self::signature_digest(issuer_key)?)
+                .map_err(CaError::Signing)?;
 
             builder.build()
         };
@@ -221,45 +692,70 @@ impl ClientCAExample {
         config: &ClientCertConfig,
         key: &PKey<Private>,
         intermediate_key: &PKey<Private>,
-        intermediate_subject_name: &X509NameRef,
-    ) -> Result<X509, ErrorStack> {
-        let subject_name = {
-            let mut n = X509Name::builder()?;
-            n.append_entry_by_nid(Nid::COMMONNAME, config.subject_name.as_str())?;
-            n.build()
-        };
+        intermediate_cert: &X509Ref,
+    ) -> Result<X509, CaError> {
+        let subject_name = config.subject.to_x509_name()?;
 
         // set basic constraints
-        let basic = BasicConstraints::new().critical().build()?;
+        let basic = BasicConstraints::new()
+            .critical()
+            .build()
+            .map_err(CaError::Extension)?;
+
+        // set extended constraints: critical, for client auth and any additional purposes
+        let extended = config
+            .extended_key_usage
+            .to_extension()
+            .map_err(CaError::Extension)?;
 
-        // set extended constraints: critical but only for client auth
-        let extended = ExtendedKeyUsage::new().critical().client_auth().build()?;
+        // key usage is only included if explicitly requested
+        let key_usage = config
+            .key_usage
+            .map(|spec| spec.to_extension())
+            .transpose()
+            .map_err(CaError::Extension)?;
 
         // set window of validity
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-        let not_before = Asn1Time::from_unix(now.sub(config.max_drift).as_secs() as i64)?;
-        let not_after = Asn1Time::from_unix(now.add(config.validity).as_secs() as i64)?;
+        let (not_before, not_after) = Self::validity_window(
+            config.max_drift,
+            config.validity,
+            config.not_before,
+            config.not_after,
+        )?;
 
         let cert = {
             let mut builder = X509::builder()?;
             builder.set_version(X509_CERT_VERSION)?;
             builder.set_subject_name(&subject_name)?;
-            builder.set_serial_number(
-                Asn1Integer::from_bn(BigNum::from_u32(config.serial_number)?.as_ref())?.as_ref(),
-            )?;
+            builder.set_serial_number(Self::serial_number(config.serial_number)?.as_ref())?;
             // set issuer since we have a parent
-            builder.set_issuer_name(intermediate_subject_name)?;
+            builder.set_issuer_name(intermediate_cert.subject_name())?;
             builder.set_not_before(&not_before)?;
             builder.set_not_after(&not_after)?;
             builder.set_pubkey(key)?;
-            // NOTE on client certificates, key usage should not be set and extended should include client auth
+            // identify the key, and the issuer's key by its own identifier
+            builder.append_extension(Self::subject_key_id(&builder)?)?;
+            builder.append_extension(Self::authority_key_id(&builder, intermediate_cert)?)?;
+            // NOTE on client certificates, key usage is optional and extended must include client auth
             // basic constraints
             builder.append_extension(basic)?;
+            // key usage
+            if let Some(key_usage) = key_usage {
+                builder.append_extension(key_usage)?;
+            }
             // extended constraints
             builder.append_extension(extended)?;
 
+            // subject alternative names, omitted entirely if there are none
+            if !config.san.is_empty() {
+                let san = Self::build_san(&config.san, &builder).map_err(CaError::Extension)?;
+                builder.append_extension(san)?;
+            }
+
             // sign the key
-            builder.sign(intermediate_key, MessageDigest::null())?;
+            builder
+                .sign(intermediate_key, Self::signature_digest(intermediate_key)?)
+                .map_err(CaError::Signing)?;
 
             builder.build()
         };
@@ -267,136 +763,760 @@ impl ClientCAExample {
         Ok(cert)
     }
 
-    pub fn verify(&self) -> Result<bool, ErrorStack> {
-        // verify that root is self-signed
-        if !self
-            .root_cert
-            .verify(self.root_cert.public_key()?.as_ref())
-            .unwrap()
-        {
-            eprintln!("Verification of self-signed root CA failed");
-            return Ok(false);
+    /// Generate a TLS server certificate, signed by the given intermediate CA.
+    pub fn generate_server_cert(
+        config: &ServerCertConfig,
+        key: &PKey<Private>,
+        intermediate_key: &PKey<Private>,
+        intermediate_cert: &X509Ref,
+    ) -> Result<X509, CaError> {
+        let subject_name = config.subject.to_x509_name()?;
+
+        // set basic constraints
+        let basic = BasicConstraints::new()
+            .critical()
+            .build()
+            .map_err(CaError::Extension)?;
+
+        // set extended constraints: critical but only for server auth
+        let extended = ExtendedKeyUsage::new()
+            .critical()
+            .server_auth()
+            .build()
+            .map_err(CaError::Extension)?;
+
+        // set window of validity
+        let (not_before, not_after) = Self::validity_window(
+            config.max_drift,
+            config.validity,
+            config.not_before,
+            config.not_after,
+        )?;
+
+        let cert = {
+            let mut builder = X509::builder()?;
+            builder.set_version(X509_CERT_VERSION)?;
+            builder.set_subject_name(&subject_name)?;
+            builder.set_serial_number(Self::serial_number(config.serial_number)?.as_ref())?;
+            // set issuer since we have a parent
+            builder.set_issuer_name(intermediate_cert.subject_name())?;
+            builder.set_not_before(&not_before)?;
+            builder.set_not_after(&not_after)?;
+            builder.set_pubkey(key)?;
+            // identify the key, and the issuer's key by its own identifier
+            builder.append_extension(Self::subject_key_id(&builder)?)?;
+            builder.append_extension(Self::authority_key_id(&builder, intermediate_cert)?)?;
+            builder.append_extension(basic)?;
+            builder.append_extension(extended)?;
+
+            // servers are identified by their SAN entries rather than the common name
+            if !config.san.is_empty() {
+                let san = Self::build_san(&config.san, &builder).map_err(CaError::Extension)?;
+                builder.append_extension(san)?;
+            }
+
+            builder
+                .sign(intermediate_key, Self::signature_digest(intermediate_key)?)
+                .map_err(CaError::Signing)?;
+
+            builder.build()
+        };
+
+        Ok(cert)
+    }
+
+    /// Generate a new server key and certificate issued by this CA's [issuer](Self::issuer).
+    pub fn issue_server_cert(
+        &self,
+        config: &ServerCertConfig,
+    ) -> Result<(PKey<Private>, X509), CaError> {
+        let key = config.key_algo.generate().map_err(CaError::KeyGeneration)?;
+        let (issuer_key, issuer_cert) = self.issuer();
+        let cert = Self::generate_server_cert(config, &key, issuer_key, issuer_cert)?;
+
+        Ok((key, cert))
+    }
+
+    /// Build a subject key identifier extension for the public key already set on the builder.
+    fn subject_key_id(builder: &X509Builder) -> Result<X509Extension, CaError> {
+        SubjectKeyIdentifier::new()
+            .build(&builder.x509v3_context(None, None))
+            .map_err(CaError::Extension)
+    }
+
+    /// Build an authority key identifier extension referring to the issuer's subject key
+    /// identifier.
+    fn authority_key_id(
+        builder: &X509Builder,
+        issuer_cert: &X509Ref,
+    ) -> Result<X509Extension, CaError> {
+        AuthorityKeyIdentifier::new()
+            .keyid(false)
+            .build(&builder.x509v3_context(Some(issuer_cert), None))
+            .map_err(CaError::Extension)
+    }
+
+    /// Compute the window of validity of a certificate.
+    ///
+    /// Explicit bounds are used as-is, otherwise they're derived from now: the window starts
+    /// `max_drift` in the past and ends `validity` in the future.
+    pub(crate) fn validity_window(
+        max_drift: Duration,
+        validity: Duration,
+        not_before: Option<SystemTime>,
+        not_after: Option<SystemTime>,
+    ) -> Result<(Asn1Time, Asn1Time), ErrorStack> {
+        let now = SystemTime::now();
+
+        Ok((
+            Self::asn1_time(not_before.unwrap_or_else(|| now.sub(max_drift)))?,
+            Self::asn1_time(not_after.unwrap_or_else(|| now.add(validity)))?,
+        ))
+    }
+
+    /// Convert a [SystemTime] to an [Asn1Time], truncating to whole seconds.
+    fn asn1_time(time: SystemTime) -> Result<Asn1Time, ErrorStack> {
+        Asn1Time::from_unix(Self::unix_secs(time))
+    }
+
+    /// Convert a [SystemTime] to whole seconds since the unix epoch, which are negative for times
+    /// before it.
+    fn unix_secs(time: SystemTime) -> i64 {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs() as i64),
         }
+    }
+
+    /// Produce the serial number for a certificate.
+    ///
+    /// An explicit serial is used as-is, otherwise a random positive serial of
+    /// [SERIAL_NUMBER_BITS] bits is generated from OpenSSL's CSPRNG.
+    fn serial_number(serial: Option<u32>) -> Result<Asn1Integer, ErrorStack> {
+        let bn = match serial {
+            Some(serial) => BigNum::from_u32(serial)?,
+            None => {
+                let mut bn = BigNum::new()?;
+                // setting the top bit guarantees a non-zero serial of the full width
+                bn.rand(SERIAL_NUMBER_BITS, MsbOption::ONE, false)?;
+                bn
+            }
+        };
 
-        if self.root_cert.issued(&self.root_cert) != X509VerifyResult::OK {
-            eprintln!(
-                "Verification that root CA issued itself failed: {}",
-                self.root_cert.issued(&self.root_cert).error_string()
-            );
-            return Ok(false);
+        Asn1Integer::from_bn(bn.as_ref())
+    }
+
+    /// Build a subject alternative name extension from the given entries.
+    fn build_san(entries: &[SanEntry], builder: &X509Builder) -> Result<X509Extension, ErrorStack> {
+        let mut san = SubjectAlternativeName::new();
+
+        for entry in entries.iter() {
+            match entry {
+                SanEntry::Dns(name) => san.dns(name),
+                SanEntry::Email(email) => san.email(email),
+                SanEntry::Ip(ip) => san.ip(ip.to_string().as_str()),
+            };
         }
 
-        // verify that intermediate is signed by root
-        if !self
-            .intermediate_cert
-            .verify(self.root_cert.public_key()?.as_ref())
-            .unwrap()
-        {
-            eprintln!("Verification of intermediate CA failed");
-            return Ok(false);
+        san.build(&builder.x509v3_context(None, None))
+    }
+
+    /// Generate a certificate revocation list (CRL) signed by this CA's [issuer](Self::issuer),
+    /// revoking the given certificates by serial number as of now.
+    ///
+    /// The CRL number is the current unix timestamp, so that successive CRLs are increasing.
+    pub fn generate_crl(&self, revoked: &[&X509]) -> Result<X509Crl, CaError> {
+        let (issuer_key, issuer_cert) = self.issuer();
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let this_update = Asn1Time::from_unix(now.as_secs() as i64)?;
+        let next_update = Asn1Time::from_unix(now.add(CRL_EXPIRY_DURATION).as_secs() as i64)?;
+
+        // identify the issuing ca by its subject key identifier, as issued certificates do
+        let authority_key_id = AuthorityKeyIdentifier::new()
+            .keyid(true)
+            .build(&X509::builder()?.x509v3_context(Some(issuer_cert), None))
+            .map_err(CaError::Extension)?;
+
+        let crl_number = CrlNumber::new(BigNum::from_u32(now.as_secs() as u32)?)
+            .and_then(|n| n.build())
+            .map_err(CaError::Extension)?;
+
+        let mut builder = X509CrlBuilder::new()?;
+        builder.set_issuer_name(issuer_cert.subject_name())?;
+        builder.set_last_update(&this_update)?;
+        builder.set_next_update(&next_update)?;
+        builder.append_extension(authority_key_id)?;
+        builder.append_extension(crl_number)?;
+
+        for cert in revoked {
+            let mut entry = X509RevokedBuilder::new()?;
+            entry.set_serial_number(cert.serial_number())?;
+            entry.set_revocation_date(&this_update)?;
+            builder.add_revoked(entry.build())?;
+        }
+
+        builder.sort()?;
+        builder
+            .sign(issuer_key, Self::signature_digest(issuer_key)?)
+            .map_err(CaError::Signing)?;
+
+        Ok(builder.build()?)
+    }
+
+    /// Check the status of a certificate against a list of revoked serial numbers, as a simplified
+    /// OCSP responder would.
+    ///
+    /// Serial numbers are [BigNum]s, as randomly-generated serials are [SERIAL_NUMBER_BITS] wide.
+    /// Only certificates issued by this CA's [issuer](Self::issuer) are known; any other
+    /// certificate is [CertStatus::Unknown] regardless of its serial number.
+    pub fn cert_status(&self, cert: &X509, revoked: &[BigNum]) -> CertStatus {
+        if Self::verify_link(ChainLink::Client, cert, self.issuer().1).is_err() {
+            return CertStatus::Unknown;
         }
 
-        if self.root_cert.issued(&self.intermediate_cert) != X509VerifyResult::OK {
-            eprintln!(
-                "Verification that root CA issued intermediate CA failed: {}",
-                self.root_cert
-                    .issued(&self.intermediate_cert)
-                    .error_string()
-            );
-            return Ok(false);
+        let serial = match cert.serial_number().to_bn() {
+            Ok(serial) => serial,
+            Err(_) => return CertStatus::Unknown,
+        };
+
+        if revoked.contains(&serial) {
+            CertStatus::Revoked
+        } else {
+            CertStatus::Good
         }
+    }
 
-        // verify that client is signed by intermediate
-        if !self
-            .client_cert
-            .verify(self.intermediate_cert.public_key()?.as_ref())
-            .unwrap()
-        {
-            eprintln!("Verification of client certificate failed");
+    /// The SHA-256 fingerprint of the root CA certificate, as uppercase hex.
+    pub fn root_fingerprint_sha256(&self) -> Result<String, ErrorStack> {
+        Self::fingerprint_sha256(&self.root_cert)
+    }
+
+    /// The SHA-256 fingerprint of the intermediate CA certificate which issues leaf certificates,
+    /// as uppercase hex.
+    ///
+    /// This is the [issuer](Self::issuer), so it's the root CA if there are no intermediates.
+    pub fn intermediate_fingerprint_sha256(&self) -> Result<String, ErrorStack> {
+        Self::fingerprint_sha256(self.issuer().1)
+    }
+
+    /// The SHA-256 fingerprint of the client certificate, as uppercase hex.
+    pub fn client_fingerprint_sha256(&self) -> Result<String, ErrorStack> {
+        Self::fingerprint_sha256(&self.client_cert)
+    }
+
+    fn fingerprint_sha256(cert: &X509) -> Result<String, ErrorStack> {
+        Ok(hex::encode_upper(cert.digest(MessageDigest::sha256())?))
+    }
+
+    /// Serialize the full certificate chain as concatenated PEM: root, intermediates, then client.
+    pub fn to_pem_chain(&self) -> Result<Vec<u8>, ErrorStack> {
+        let mut v = self.root_cert.to_pem()?;
+
+        for (_, cert) in self.intermediates.iter() {
+            v.extend(cert.to_pem()?);
         }
 
-        if self.intermediate_cert.issued(&self.client_cert) != X509VerifyResult::OK {
-            eprintln!(
-                "Verification that intermediate CA issued client certificate failed: {}",
-                self.intermediate_cert
-                    .issued(&self.client_cert)
-                    .error_string()
-            );
-            return Ok(false);
+        v.extend(self.client_cert.to_pem()?);
+        Ok(v)
+    }
+
+    /// Serialize the client key and certificate into a DER-encoded PKCS#12 archive protected by
+    /// the given password, including the root and intermediate certificates as the CA stack.
+    pub fn to_pkcs12(&self, password: &str) -> Result<Vec<u8>, ErrorStack> {
+        let mut cert_stack = Stack::new()?;
+        cert_stack.push(self.root_cert.clone())?;
+
+        for (_, cert) in self.intermediates.iter() {
+            cert_stack.push(cert.clone())?;
         }
 
-        Ok(true)
+        let mut builder = Pkcs12::builder();
+        builder.pkey(&self.client_key);
+        builder.cert(&self.client_cert);
+        builder.ca(cert_stack);
+
+        builder.build2(password)?.to_der()
+    }
+
+    /// Verify the client certificate against the chain the way a TLS stack would.
+    ///
+    /// Unlike [ClientCAExample::verify], this uses an [X509StoreContext] trusting only the root CA,
+    /// with the intermediate CAs supplied as untrusted chain certificates. This checks signatures,
+    /// validity windows, and basic constraints including path length. Returns a
+    /// [CaError::Untrusted] carrying the store context's error if the chain isn't trusted.
+    pub fn verify_chain(&self) -> Result<(), CaError> {
+        self.verify_chain_with(None)
+    }
+
+    /// Verify the certificate chain as in [ClientCAExample::verify_chain], but as of the given
+    /// time rather than now.
+    pub fn verify_chain_at(&self, time: SystemTime) -> Result<(), CaError> {
+        self.verify_chain_with(Some(time))
+    }
+
+    fn verify_chain_with(&self, time: Option<SystemTime>) -> Result<(), CaError> {
+        let store = {
+            let mut builder = X509StoreBuilder::new()?;
+            builder.add_cert(self.root_cert.clone())?;
+
+            if let Some(time) = time {
+                let mut param = X509VerifyParam::new()?;
+                param.set_time(Self::unix_secs(time) as _);
+                builder.set_param(&param)?;
+            }
+
+            builder.build()
+        };
+
+        let mut chain = Stack::new()?;
+
+        for (_, cert) in self.intermediates.iter() {
+            chain.push(cert.clone())?;
+        }
+
+        verify_in_store(&store, &self.client_cert, &chain)
+    }
+
+    /// Verify that each certificate in the chain was signed and issued by its parent.
+    ///
+    /// Returns a [CaError::Verification] identifying the first link which failed.
+    pub fn verify(&self) -> Result<(), CaError> {
+        // verify that root is self-signed
+        Self::verify_link(ChainLink::Root, &self.root_cert, &self.root_cert)?;
+
+        // verify that each intermediate is signed by its parent
+        let mut issuer = &self.root_cert;
+
+        for (index, (_, cert)) in self.intermediates.iter().enumerate() {
+            Self::verify_link(ChainLink::Intermediate(index), cert, issuer)?;
+            issuer = cert;
+        }
+
+        // verify that client is signed by the last intermediate
+        Self::verify_link(ChainLink::Client, &self.client_cert, issuer)?;
+
+        Ok(())
+    }
+
+    /// Verify that `cert` was signed by and issued by `issuer`.
+    fn verify_link(link: ChainLink, cert: &X509, issuer: &X509) -> Result<(), CaError> {
+        match cert.verify(issuer.public_key()?.as_ref()) {
+            Ok(true) => {}
+            Ok(false) => {
+                return Err(CaError::Verification {
+                    link,
+                    reason: "signature does not match issuer public key".into(),
+                })
+            }
+            Err(e) => {
+                return Err(CaError::Verification {
+                    link,
+                    reason: e.to_string(),
+                })
+            }
+        }
+
+        let issued = issuer.issued(cert);
+
+        if issued != X509VerifyResult::OK {
+            return Err(CaError::Verification {
+                link,
+                reason: issued.error_string().into(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Verify that a server certificate was issued by this CA's [issuer](Self::issuer).
+    pub fn verify_server(&self, server_cert: &X509) -> Result<(), CaError> {
+        Self::verify_link(ChainLink::Server, server_cert, self.issuer().1)
     }
 }
 
 #[derive(Debug)]
 pub struct ClientCAConfig {
     pub root_config: CAConfig,
-    pub intermediate_config: CAConfig,
+    /// The intermediate CA layers, ordered from the one signed by the root downwards.
+    pub intermediate_configs: Vec<CAConfig>,
     pub client_config: ClientCertConfig,
 }
 
 impl Default for ClientCAConfig {
     fn default() -> Self {
         Self {
-            root_config: CAConfig {
-                ca_type: CAType::Root,
-                subject_name: X509_ROOT_SUBJECT_NAME.into(),
-                max_drift: NOT_BEFORE_DRIFT_DURATION,
-                validity: ROOT_EXPIRY_DURATION,
-                serial_number: 1000,
-            },
-            intermediate_config: CAConfig {
-                ca_type: CAType::Intermediate,
-                subject_name: X509_INTERMEDIATE_SUBJECT_NAME.into(),
-                max_drift: NOT_BEFORE_DRIFT_DURATION,
-                validity: INTERMEDIATE_EXPIRY_DURATION,
-                serial_number: 2000,
-            },
+            root_config: CAConfig::root(),
+            intermediate_configs: vec![CAConfig::intermediate()],
             client_config: Default::default(),
         }
     }
 }
 
+impl ClientCAConfig {
+    /// Create a builder for overriding individual settings of the default configuration.
+    pub fn builder() -> ClientCAConfigBuilder {
+        ClientCAConfigBuilder::default()
+    }
+}
+
+/// Fluently overrides settings of the default [ClientCAConfig], leaving everything else as-is.
+#[derive(Debug, Default)]
+pub struct ClientCAConfigBuilder {
+    key_algo: Option<KeyAlgorithm>,
+    intermediate_layers: Option<usize>,
+    root_validity: Option<Duration>,
+    intermediate_validity: Option<Duration>,
+    client_validity: Option<Duration>,
+    client_subject: Option<String>,
+}
+
+impl ClientCAConfigBuilder {
+    /// Set the key algorithm used by every layer of the CA, including the client certificate.
+    pub fn key_algo(mut self, key_algo: KeyAlgorithm) -> Self {
+        self.key_algo = Some(key_algo);
+        self
+    }
+
+    /// Set the number of intermediate CA layers, each using the default intermediate
+    /// configuration; zero issues the client certificate directly from the root.
+    pub fn intermediate_layers(mut self, layers: usize) -> Self {
+        self.intermediate_layers = Some(layers);
+        self
+    }
+
+    /// Set how long the root CA certificate is valid for.
+    pub fn root_validity(mut self, validity: Duration) -> Self {
+        self.root_validity = Some(validity);
+        self
+    }
+
+    /// Set how long each intermediate CA certificate is valid for.
+    pub fn intermediate_validity(mut self, validity: Duration) -> Self {
+        self.intermediate_validity = Some(validity);
+        self
+    }
+
+    /// Set how long the client certificate is valid for.
+    pub fn client_validity(mut self, validity: Duration) -> Self {
+        self.client_validity = Some(validity);
+        self
+    }
+
+    /// Set the common name of the client certificate's subject.
+    pub fn client_subject<S: Into<String>>(mut self, common_name: S) -> Self {
+        self.client_subject = Some(common_name.into());
+        self
+    }
+
+    /// Build the configuration, using the defaults for anything not explicitly set.
+    pub fn build(self) -> ClientCAConfig {
+        let mut config = ClientCAConfig::default();
+
+        if let Some(layers) = self.intermediate_layers {
+            config.intermediate_configs = (0..layers).map(|_| CAConfig::intermediate()).collect();
+        }
+
+        if let Some(key_algo) = self.key_algo {
+            config.root_config.key_algo = key_algo;
+            config.client_config.key_algo = key_algo;
+
+            for intermediate in config.intermediate_configs.iter_mut() {
+                intermediate.key_algo = key_algo;
+            }
+        }
+
+        if let Some(validity) = self.root_validity {
+            config.root_config.validity = validity;
+        }
+
+        if let Some(validity) = self.intermediate_validity {
+            for intermediate in config.intermediate_configs.iter_mut() {
+                intermediate.validity = validity;
+            }
+        }
+
+        if let Some(validity) = self.client_validity {
+            config.client_config.validity = validity;
+        }
+
+        if let Some(common_name) = self.client_subject {
+            config.client_config.subject = DistinguishedName::common_name(common_name);
+        }
+
+        config
+    }
+}
+
 #[derive(Debug)]
 pub struct CAConfig {
-    pub ca_type: CAType,
-    pub subject_name: String,
+    pub key_algo: KeyAlgorithm,
+    pub subject: DistinguishedName,
     pub max_drift: Duration,
     pub validity: Duration,
-    pub serial_number: u32,
+    /// An explicit start of the window of validity, overriding `max_drift` if set.
+    pub not_before: Option<SystemTime>,
+    /// An explicit end of the window of validity, overriding `validity` if set.
+    pub not_after: Option<SystemTime>,
+    /// The certificate serial number; if unset, a random serial is generated.
+    pub serial_number: Option<u32>,
 }
 
-#[derive(Debug)]
-pub enum CAType {
-    Root,
-    Intermediate,
+impl CAConfig {
+    /// The default configuration for the root CA.
+    pub fn root() -> Self {
+        Self {
+            key_algo: KeyAlgorithm::default(),
+            subject: DistinguishedName::with_default_org(X509_ROOT_SUBJECT_NAME),
+            max_drift: NOT_BEFORE_DRIFT_DURATION,
+            validity: ROOT_EXPIRY_DURATION,
+            not_before: None,
+            not_after: None,
+            serial_number: None,
+        }
+    }
+
+    /// The default configuration for an intermediate CA.
+    pub fn intermediate() -> Self {
+        Self {
+            key_algo: KeyAlgorithm::default(),
+            subject: DistinguishedName::with_default_org(X509_INTERMEDIATE_SUBJECT_NAME),
+            max_drift: NOT_BEFORE_DRIFT_DURATION,
+            validity: INTERMEDIATE_EXPIRY_DURATION,
+            not_before: None,
+            not_after: None,
+            serial_number: None,
+        }
+    }
+}
+
+/// The subject distinguished name of a certificate.
+///
+/// Only populated fields are included in the resulting X509 name.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct DistinguishedName {
+    pub common_name: Option<String>,
+    pub country: Option<String>,
+    pub state: Option<String>,
+    pub locality: Option<String>,
+    pub organization: Option<String>,
+    pub organizational_unit: Option<String>,
 }
 
-impl CAType {
-    pub fn path_length(&self) -> u32 {
-        match &self {
-            Self::Root => 1,
-            Self::Intermediate => 0,
+impl DistinguishedName {
+    /// Create a distinguished name containing only a common name.
+    pub fn common_name<S: Into<String>>(common_name: S) -> Self {
+        Self {
+            common_name: Some(common_name.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Create a distinguished name with the given common name and the default country,
+    /// organization, and organizational unit.
+    pub fn with_default_org<S: Into<String>>(common_name: S) -> Self {
+        Self {
+            common_name: Some(common_name.into()),
+            country: Some(X509_COUNTRY_NAME.into()),
+            organization: Some(X509_ORGANIZATION_NAME.into()),
+            organizational_unit: Some(X509_ORGANIZATIONAL_UNIT_NAME.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Build an [X509Name] from the populated fields.
+    pub fn to_x509_name(&self) -> Result<X509Name, ErrorStack> {
+        let mut n = X509Name::builder()?;
+
+        for (nid, value) in [
+            (Nid::COMMONNAME, &self.common_name),
+            (Nid::COUNTRYNAME, &self.country),
+            (Nid::STATEORPROVINCENAME, &self.state),
+            (Nid::LOCALITYNAME, &self.locality),
+            (Nid::ORGANIZATIONNAME, &self.organization),
+            (Nid::ORGANIZATIONALUNITNAME, &self.organizational_unit),
+        ] {
+            if let Some(value) = value {
+                n.append_entry_by_nid(nid, value.as_str())?;
+            }
+        }
+
+        Ok(n.build())
+    }
+}
+
+/// The key algorithm to use for a given layer of the CA.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum KeyAlgorithm {
+    #[default]
+    Ed25519,
+    Ed448,
+    Prime256v1,
+    Secp384r1,
+    /// RSA with the given modulus size, such as 2048, 3072, or 4096 bits, and public exponent.
+    Rsa {
+        bits: u32,
+        exponent: u32,
+    },
+}
+
+impl KeyAlgorithm {
+    /// RSA with the given modulus size and the standard public exponent of [RSA_EXPONENT].
+    pub fn rsa(bits: u32) -> Self {
+        Self::Rsa {
+            bits,
+            exponent: RSA_EXPONENT,
+        }
+    }
+
+    /// Generate a new private key using this algorithm.
+    pub fn generate(&self) -> Result<PKey<Private>, ErrorStack> {
+        match self {
+            Self::Ed25519 => PKey::generate_ed25519(),
+            Self::Ed448 => PKey::generate_ed448(),
+            Self::Prime256v1 => PKey::ec_gen("prime256v1"),
+            Self::Secp384r1 => PKey::ec_gen("secp384r1"),
+            Self::Rsa { bits, exponent } => PKey::from_rsa(Rsa::generate_with_e(
+                *bits,
+                BigNum::from_u32(*exponent)?.as_ref(),
+            )?),
         }
     }
 }
 
 #[derive(Debug)]
 pub struct ClientCertConfig {
-    pub subject_name: String,
+    pub key_algo: KeyAlgorithm,
+    pub subject: DistinguishedName,
+    /// Subject alternative names to include; if empty, the extension is omitted.
+    pub san: Vec<SanEntry>,
+    /// Key usage bits to include; if unset, the extension is omitted.
+    pub key_usage: Option<KeyUsageSpec>,
+    /// Purposes to include in the extended key usage alongside client auth.
+    pub extended_key_usage: ExtendedKeyUsageSpec,
     pub max_drift: Duration,
     pub validity: Duration,
-    pub serial_number: u32,
+    /// An explicit start of the window of validity, overriding `max_drift` if set.
+    pub not_before: Option<SystemTime>,
+    /// An explicit end of the window of validity, overriding `validity` if set.
+    pub not_after: Option<SystemTime>,
+    /// The certificate serial number; if unset, a random serial is generated.
+    pub serial_number: Option<u32>,
+}
+
+/// The bits of a critical key usage extension on a client certificate.
+///
+/// At least one bit must be set, as an empty key usage extension is invalid.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct KeyUsageSpec {
+    pub digital_signature: bool,
+    pub non_repudiation: bool,
+    pub key_encipherment: bool,
+    pub key_agreement: bool,
+}
+
+impl KeyUsageSpec {
+    fn to_extension(self) -> Result<X509Extension, ErrorStack> {
+        let mut usage = KeyUsage::new();
+        usage.critical();
+
+        if self.digital_signature {
+            usage.digital_signature();
+        }
+
+        if self.non_repudiation {
+            usage.non_repudiation();
+        }
+
+        if self.key_encipherment {
+            usage.key_encipherment();
+        }
+
+        if self.key_agreement {
+            usage.key_agreement();
+        }
+
+        usage.build()
+    }
+}
+
+/// Purposes to include in a client certificate's critical extended key usage extension, in
+/// addition to client auth, which is always present.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct ExtendedKeyUsageSpec {
+    pub email_protection: bool,
+    pub time_stamping: bool,
+}
+
+impl ExtendedKeyUsageSpec {
+    fn to_extension(self) -> Result<X509Extension, ErrorStack> {
+        let mut usage = ExtendedKeyUsage::new();
+        usage.critical().client_auth();
+
+        if self.email_protection {
+            usage.email_protection();
+        }
+
+        if self.time_stamping {
+            usage.time_stamping();
+        }
+
+        usage.build()
+    }
+}
+
+/// An entry in a certificate's subject alternative name extension.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SanEntry {
+    Dns(String),
+    Email(String),
+    Ip(IpAddr),
 }
 
 impl Default for ClientCertConfig {
     fn default() -> Self {
         Self {
-            subject_name: X509_CLIENT_SUBJECT_NAME.into(),
+            key_algo: KeyAlgorithm::default(),
+            subject: DistinguishedName::common_name(X509_CLIENT_SUBJECT_NAME),
+            san: Vec::new(),
+            key_usage: None,
+            extended_key_usage: ExtendedKeyUsageSpec::default(),
             max_drift: NOT_BEFORE_DRIFT_DURATION,
             validity: CLIENT_EXPIRY_DURATION,
-            serial_number: 3000,
+            not_before: None,
+            not_after: None,
+            serial_number: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ServerCertConfig {
+    pub key_algo: KeyAlgorithm,
+    pub subject: DistinguishedName,
+    /// Subject alternative names identifying the server; if empty, the extension is omitted.
+    pub san: Vec<SanEntry>,
+    pub max_drift: Duration,
+    pub validity: Duration,
+    /// An explicit start of the window of validity, overriding `max_drift` if set.
+    pub not_before: Option<SystemTime>,
+    /// An explicit end of the window of validity, overriding `validity` if set.
+    pub not_after: Option<SystemTime>,
+    /// The certificate serial number; if unset, a random serial is generated.
+    pub serial_number: Option<u32>,
+}
+
+impl Default for ServerCertConfig {
+    fn default() -> Self {
+        Self {
+            key_algo: KeyAlgorithm::default(),
+            subject: DistinguishedName::common_name(X509_SERVER_SUBJECT_NAME),
+            san: vec![SanEntry::Dns("localhost".into())],
+            max_drift: NOT_BEFORE_DRIFT_DURATION,
+            validity: SERVER_EXPIRY_DURATION,
+            not_before: None,
+            not_after: None,
+            serial_number: None,
         }
     }
 }
@@ -0,0 +1,1036 @@
+use super::{
+    describe_cert, is_currently_valid, load_pkcs12, split_pem_chain, verify_against_ca, CAConfig,
+    CaError, CertStatus, ChainLink, ClientCAConfig, ClientCAExample, DistinguishedName,
+    ExtendedKeyUsageSpec, KeyAlgorithm, KeyUsageSpec, SanEntry, ServerCertConfig,
+    CLIENT_EXPIRY_DURATION, INTERMEDIATE_EXPIRY_DURATION, ROOT_EXPIRY_DURATION,
+    X509_ORGANIZATIONAL_UNIT_NAME,
+};
+
+use openssl::asn1::Asn1Time;
+use openssl::bn::BigNum;
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkcs12::Pkcs12;
+use openssl::pkey::Id;
+use openssl::x509::{CrlStatus, X509Crl, X509VerifyResult, X509};
+use std::net::{IpAddr, Ipv4Addr};
+use std::ops::{Add, Sub};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[test]
+fn test_generate_default() {
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+
+    assert_eq!(Id::ED25519, ca.root_key.id());
+    assert_eq!(Id::ED25519, ca.intermediates[0].0.id());
+    assert_eq!(Id::ED25519, ca.client_key.id());
+    ca.verify().expect("unable to verify CA");
+}
+
+#[test]
+fn test_generate_mixed_algorithms() {
+    let mut config = ClientCAConfig::default();
+    config.root_config.key_algo = KeyAlgorithm::rsa(2048);
+    config.intermediate_configs[0].key_algo = KeyAlgorithm::Secp384r1;
+    config.client_config.key_algo = KeyAlgorithm::Prime256v1;
+
+    let ca = ClientCAExample::generate(config).expect("unable to generate CA");
+
+    assert_eq!(Id::RSA, ca.root_key.id());
+    assert_eq!(Id::EC, ca.intermediates[0].0.id());
+    assert_eq!(Id::EC, ca.client_key.id());
+    ca.verify().expect("unable to verify CA");
+}
+
+#[test]
+fn test_issuer_digest() {
+    let signature_nid = |cert: &X509| cert.signature_algorithm().object().nid();
+
+    // EC issuers hash with a digest matching their curve, even when signing an EdDSA key
+    let mut config = ClientCAConfig::default();
+    config.root_config.key_algo = KeyAlgorithm::Secp384r1;
+    config.intermediate_configs[0].key_algo = KeyAlgorithm::Prime256v1;
+    config.client_config.key_algo = KeyAlgorithm::Ed25519;
+
+    let ca = ClientCAExample::generate(config).expect("unable to generate CA");
+
+    assert_eq!(Nid::ECDSA_WITH_SHA384, signature_nid(&ca.root_cert));
+    assert_eq!(
+        Nid::ECDSA_WITH_SHA384,
+        signature_nid(&ca.intermediates[0].1)
+    );
+    assert_eq!(Nid::ECDSA_WITH_SHA256, signature_nid(&ca.client_cert));
+    ca.verify().expect("unable to verify CA");
+
+    // EdDSA issuers sign without a digest, whatever the key they're signing
+    let mut config = ClientCAConfig::default();
+    config.intermediate_configs[0].key_algo = KeyAlgorithm::rsa(2048);
+
+    let ca = ClientCAExample::generate(config).expect("unable to generate CA");
+
+    assert_eq!(
+        Nid::from_raw(Id::ED25519.as_raw()),
+        signature_nid(&ca.intermediates[0].1)
+    );
+    assert_eq!(Nid::SHA256WITHRSAENCRYPTION, signature_nid(&ca.client_cert));
+    ca.verify().expect("unable to verify CA");
+}
+
+#[test]
+fn test_generate_ed448() {
+    let mut config = ClientCAConfig::default();
+    config.root_config.key_algo = KeyAlgorithm::Ed448;
+    config.intermediate_configs[0].key_algo = KeyAlgorithm::Ed448;
+    config.client_config.key_algo = KeyAlgorithm::Ed448;
+
+    let ca = ClientCAExample::generate(config).expect("unable to generate CA");
+
+    assert_eq!(Id::ED448, ca.client_key.id());
+    ca.verify().expect("unable to verify CA");
+}
+
+#[test]
+fn test_client_san() {
+    let mut config = ClientCAConfig::default();
+    config.client_config.san = vec![
+        SanEntry::Dns("client.example.com".into()),
+        SanEntry::Email("client@example.com".into()),
+        SanEntry::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+    ];
+
+    let ca = ClientCAExample::generate(config).expect("unable to generate CA");
+    let san = ca
+        .client_cert
+        .subject_alt_names()
+        .expect("client certificate has no SAN extension");
+
+    let dns: Vec<&str> = san.iter().filter_map(|n| n.dnsname()).collect();
+    let email: Vec<&str> = san.iter().filter_map(|n| n.email()).collect();
+    let ip: Vec<&[u8]> = san.iter().filter_map(|n| n.ipaddress()).collect();
+
+    assert_eq!(vec!["client.example.com"], dns);
+    assert_eq!(vec!["client@example.com"], email);
+    assert_eq!(vec![&[127, 0, 0, 1][..]], ip);
+    ca.verify().expect("unable to verify CA");
+}
+
+#[test]
+fn test_client_san_empty() {
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+
+    assert!(ca.client_cert.subject_alt_names().is_none());
+}
+
+#[test]
+fn test_to_pem_chain() {
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+
+    let chain = ca.to_pem_chain().expect("unable to serialize chain");
+    let certs = X509::stack_from_pem(&chain).expect("unable to parse chain");
+
+    assert_eq!(3, certs.len());
+    assert_eq!(ca.root_cert.to_der().unwrap(), certs[0].to_der().unwrap());
+    assert_eq!(
+        ca.intermediates[0].1.to_der().unwrap(),
+        certs[1].to_der().unwrap()
+    );
+    assert_eq!(ca.client_cert.to_der().unwrap(), certs[2].to_der().unwrap());
+}
+
+#[test]
+fn test_to_pkcs12() {
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+
+    let der = ca.to_pkcs12("hunter2").expect("unable to serialize pkcs12");
+    let parsed = Pkcs12::from_der(&der)
+        .expect("unable to parse pkcs12")
+        .parse2("hunter2")
+        .expect("unable to decrypt pkcs12");
+
+    let cert = parsed.cert.expect("pkcs12 has no client certificate");
+    let key = parsed.pkey.expect("pkcs12 has no client key");
+    let chain = parsed.ca.expect("pkcs12 has no CA stack");
+
+    assert_eq!(ca.client_cert.to_der().unwrap(), cert.to_der().unwrap());
+    assert!(key.public_eq(&ca.client_key));
+    assert_eq!(2, chain.len());
+}
+
+#[test]
+fn test_load_pkcs12() {
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+    let der = ca.to_pkcs12("hunter2").expect("unable to serialize pkcs12");
+
+    let identity = load_pkcs12(&der, "hunter2").expect("unable to load pkcs12");
+    let fingerprint =
+        |cert: &X509| hex::encode_upper(cert.digest(MessageDigest::sha256()).unwrap());
+
+    assert_eq!(
+        ca.client_fingerprint_sha256().unwrap(),
+        fingerprint(&identity.cert)
+    );
+    assert!(identity.key.public_eq(&ca.client_key));
+
+    let mut chain: Vec<String> = identity.chain.iter().map(fingerprint).collect();
+    chain.sort();
+
+    let mut expected = vec![
+        ca.root_fingerprint_sha256().unwrap(),
+        ca.intermediate_fingerprint_sha256().unwrap(),
+    ];
+    expected.sort();
+
+    assert_eq!(expected, chain);
+
+    assert!(matches!(
+        load_pkcs12(&der, "hunter3"),
+        Err(CaError::Pkcs12(_))
+    ));
+    assert!(matches!(
+        load_pkcs12(b"not a pkcs12 bundle", "hunter2"),
+        Err(CaError::Pkcs12(_))
+    ));
+}
+
+#[test]
+fn test_default_subjects() {
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+
+    assert_eq!(4, ca.root_cert.subject_name().entries().count());
+    assert_eq!(4, ca.intermediates[0].1.subject_name().entries().count());
+    assert_eq!(1, ca.client_cert.subject_name().entries().count());
+}
+
+#[test]
+fn test_custom_subject() {
+    let mut config = ClientCAConfig::default();
+    config.root_config.subject = DistinguishedName {
+        common_name: Some("Custom Root".into()),
+        country: Some("CA".into()),
+        ..Default::default()
+    };
+
+    let ca = ClientCAExample::generate(config).expect("unable to generate CA");
+    let subject = ca.root_cert.subject_name();
+
+    assert_eq!(2, subject.entries().count());
+    assert_eq!(
+        "Custom Root",
+        subject
+            .entries_by_nid(Nid::COMMONNAME)
+            .next()
+            .unwrap()
+            .data()
+            .to_string()
+            .unwrap()
+    );
+    // the intermediate's issuer must reflect the custom root subject
+    assert_eq!(2, ca.intermediates[0].1.issuer_name().entries().count());
+    ca.verify().expect("unable to verify CA");
+}
+
+#[test]
+fn test_server_cert() {
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+
+    let (key, cert) = ca
+        .issue_server_cert(&ServerCertConfig::default())
+        .expect("unable to generate server certificate");
+
+    assert!(cert.public_key().unwrap().public_eq(&key));
+    assert_eq!(
+        vec!["localhost"],
+        cert.subject_alt_names()
+            .expect("server certificate has no SAN extension")
+            .iter()
+            .filter_map(|n| n.dnsname())
+            .collect::<Vec<&str>>()
+    );
+    ca.verify_server(&cert).expect("unable to verify server");
+
+    // a server certificate issued by another CA must not verify
+    let other =
+        ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+    let (_, foreign) = other
+        .issue_server_cert(&ServerCertConfig::default())
+        .expect("unable to generate server certificate");
+
+    assert!(matches!(
+        ca.verify_server(&foreign),
+        Err(CaError::Verification {
+            link: ChainLink::Server,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_random_serials() {
+    let a = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+    let b = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+
+    let serial = |cert: &X509| cert.serial_number().to_bn().unwrap();
+
+    assert_ne!(serial(&a.root_cert), serial(&b.root_cert));
+    assert_ne!(serial(&a.root_cert), serial(&a.intermediates[0].1));
+    assert_ne!(serial(&a.intermediates[0].1), serial(&a.client_cert));
+    assert_eq!(128, serial(&a.root_cert).num_bits());
+}
+
+#[test]
+fn test_explicit_serial() {
+    let mut config = ClientCAConfig::default();
+    config.root_config.serial_number = Some(1000);
+
+    let ca = ClientCAExample::generate(config).expect("unable to generate CA");
+
+    assert_eq!(
+        "1000",
+        ca.root_cert
+            .serial_number()
+            .to_bn()
+            .unwrap()
+            .to_dec_str()
+            .unwrap()
+            .to_string()
+    );
+}
+
+#[test]
+fn test_verify_chain() {
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+
+    ca.verify_chain().expect("unable to verify chain");
+}
+
+#[test]
+fn test_verify_chain_expired() {
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+
+    // the client certificate is only valid for an hour, so two hours from now it has expired
+    let later = SystemTime::now().add(Duration::from_secs(60 * 60 * 2));
+
+    match ca.verify_chain_at(later) {
+        Err(CaError::Untrusted { depth, reason }) => {
+            assert_eq!(0, depth);
+            assert_eq!("certificate has expired", reason.error_string());
+        }
+        other => panic!("expected an untrusted error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_verify_chain_before_epoch() {
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+
+    // a time before the unix epoch is long before any of the certificates became valid
+    let earlier = UNIX_EPOCH.sub(Duration::from_secs(60 * 60 * 24));
+
+    match ca.verify_chain_at(earlier) {
+        Err(CaError::Untrusted { reason, .. }) => {
+            // newer openssl releases append a hint about the system clock
+            assert!(reason
+                .error_string()
+                .starts_with("certificate is not yet valid"));
+        }
+        other => panic!("expected an untrusted error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_verify_chain_untrusted_root() {
+    let a = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+    let mut b =
+        ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+
+    // swap in a root which did not issue the intermediate
+    b.root_cert = a.root_cert.clone();
+
+    match b.verify_chain() {
+        Err(CaError::Untrusted { depth, reason }) => {
+            // the intermediate, one above the client, can't be chained up to the trusted root
+            assert_eq!(1, depth);
+            assert_ne!(X509VerifyResult::OK, reason);
+        }
+        other => panic!("expected an untrusted error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_verify_foreign_intermediate() {
+    let mut ca =
+        ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+    let other =
+        ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+
+    ca.intermediates[0].1 = other.intermediates[0].1.clone();
+
+    assert!(matches!(
+        ca.verify(),
+        Err(CaError::Verification {
+            link: ChainLink::Intermediate(0),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_verify_foreign_client() {
+    let mut ca =
+        ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+    let other =
+        ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+
+    ca.client_cert = other.client_cert;
+
+    let err = ca
+        .verify()
+        .expect_err("foreign client certificate verified");
+
+    assert!(matches!(
+        err,
+        CaError::Verification {
+            link: ChainLink::Client,
+            ..
+        }
+    ));
+    assert!(err
+        .to_string()
+        .starts_with("verification of client certificate failed"));
+}
+
+#[test]
+fn test_key_generation_error() {
+    let mut config = ClientCAConfig::default();
+    config.root_config.key_algo = KeyAlgorithm::rsa(0);
+
+    assert!(matches!(
+        ClientCAExample::generate(config),
+        Err(CaError::KeyGeneration(_))
+    ));
+}
+
+#[test]
+fn test_multiple_intermediates() {
+    let mut config = ClientCAConfig::default();
+    config.intermediate_configs.push(CAConfig {
+        subject: DistinguishedName::with_default_org("Rust Example Issuing CA"),
+        ..CAConfig::intermediate()
+    });
+
+    let ca = ClientCAExample::generate(config).expect("unable to generate CA");
+
+    assert_eq!(2, ca.intermediates.len());
+    assert_eq!(Some(2), ca.root_cert.pathlen());
+    assert_eq!(Some(1), ca.intermediates[0].1.pathlen());
+    assert_eq!(Some(0), ca.intermediates[1].1.pathlen());
+
+    ca.verify().expect("unable to verify CA");
+    ca.verify_chain().expect("unable to verify chain");
+
+    // the client must be issued by the last intermediate
+    assert_eq!(
+        X509VerifyResult::OK,
+        ca.intermediates[1].1.issued(&ca.client_cert)
+    );
+
+    let chain = X509::stack_from_pem(&ca.to_pem_chain().expect("unable to serialize chain"))
+        .expect("unable to parse chain");
+    assert_eq!(4, chain.len());
+}
+
+#[test]
+fn test_generate_crl() {
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+    let (_, other) = ca
+        .issue_server_cert(&ServerCertConfig::default())
+        .expect("unable to issue server cert");
+
+    let crl = ca
+        .generate_crl(&[&ca.client_cert])
+        .expect("unable to generate CRL");
+
+    // round trip through der to make sure it parses back
+    let crl = X509Crl::from_der(&crl.to_der().unwrap()).expect("unable to parse CRL");
+
+    let (issuer_key, issuer_cert) = ca.issuer();
+    assert!(crl.verify(issuer_key).unwrap());
+    assert_eq!(
+        issuer_cert.subject_name().to_der().unwrap(),
+        crl.issuer_name().to_der().unwrap()
+    );
+
+    // the authority key identifier carries the issuer's subject key identifier
+    let key_id = issuer_cert
+        .subject_key_id()
+        .expect("issuer has no subject key identifier")
+        .as_slice();
+    assert!(crl
+        .to_der()
+        .unwrap()
+        .windows(key_id.len())
+        .any(|w| w == key_id));
+
+    let revoked = crl.get_revoked().expect("CRL has no revoked certificates");
+    assert_eq!(1, revoked.len());
+    assert_eq!(
+        ca.client_cert.serial_number().to_bn().unwrap(),
+        revoked.get(0).unwrap().serial_number().to_bn().unwrap()
+    );
+
+    assert!(matches!(
+        crl.get_by_cert(&ca.client_cert),
+        CrlStatus::Revoked(_)
+    ));
+    assert!(matches!(crl.get_by_cert(&other), CrlStatus::NotRevoked));
+}
+
+/// Serialize a generated CA to PEM as it would be written to disk.
+fn to_pem_parts(ca: &ClientCAExample) -> [Vec<u8>; 6] {
+    let (intermediate_key, intermediate_cert) = &ca.intermediates[0];
+
+    [
+        ca.root_key.private_key_to_pem_pkcs8().unwrap(),
+        ca.root_cert.to_pem().unwrap(),
+        intermediate_key.private_key_to_pem_pkcs8().unwrap(),
+        intermediate_cert.to_pem().unwrap(),
+        ca.client_key.private_key_to_pem_pkcs8().unwrap(),
+        ca.client_cert.to_pem().unwrap(),
+    ]
+}
+
+#[test]
+fn test_from_pem_round_trip() {
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+    let [rk, rc, ik, ic, ck, cc] = to_pem_parts(&ca);
+
+    let loaded =
+        ClientCAExample::from_pem(&rk, &rc, &ik, &ic, &ck, &cc).expect("unable to load CA");
+
+    loaded.verify().expect("unable to verify loaded CA");
+    loaded.verify_chain().expect("unable to verify chain");
+    assert_eq!(ca.to_pem_chain().unwrap(), loaded.to_pem_chain().unwrap());
+}
+
+#[test]
+fn test_from_pem_errors() {
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+    let [rk, rc, ik, ic, ck, cc] = to_pem_parts(&ca);
+
+    let err = ClientCAExample::from_pem(&rk, &rc, &ik, b"not a certificate", &ck, &cc)
+        .err()
+        .expect("invalid PEM was accepted");
+
+    assert!(matches!(
+        err,
+        CaError::Pem {
+            link: ChainLink::Intermediate(0),
+            part: "certificate",
+            ..
+        }
+    ));
+    assert_eq!(
+        "unable to parse intermediate #0 certificate PEM",
+        err.to_string()
+    );
+
+    // swapping the client key for the root key must be caught
+    let err = ClientCAExample::from_pem(&rk, &rc, &ik, &ic, &rk, &cc)
+        .err()
+        .expect("mismatched key was accepted");
+
+    assert!(matches!(err, CaError::KeyMismatch(ChainLink::Client)));
+}
+
+fn serials(serials: &[u32]) -> Vec<BigNum> {
+    serials
+        .iter()
+        .map(|&n| BigNum::from_u32(n).expect("unable to create serial"))
+        .collect()
+}
+
+#[test]
+fn test_cert_status_good() {
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+
+    assert_eq!(CertStatus::Good, ca.cert_status(&ca.client_cert, &[]));
+    assert_eq!(
+        CertStatus::Good,
+        ca.cert_status(&ca.client_cert, &serials(&[1, 2, 3]))
+    );
+}
+
+#[test]
+fn test_cert_status_revoked() {
+    let mut config = ClientCAConfig::default();
+    config.client_config.serial_number = Some(4242);
+
+    let ca = ClientCAExample::generate(config).expect("unable to generate CA");
+
+    assert_eq!(
+        CertStatus::Revoked,
+        ca.cert_status(&ca.client_cert, &serials(&[1, 4242]))
+    );
+
+    // a default certificate has a full-width random serial, which can be revoked all the same
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+    let serial = ca
+        .client_cert
+        .serial_number()
+        .to_bn()
+        .expect("unable to read serial");
+
+    assert!(serial.num_bits() > 64);
+    assert_eq!(
+        CertStatus::Revoked,
+        ca.cert_status(&ca.client_cert, &[BigNum::from_u32(1).unwrap(), serial])
+    );
+}
+
+#[test]
+fn test_cert_status_unknown() {
+    let mut config = ClientCAConfig::default();
+    config.client_config.serial_number = Some(4242);
+
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+    let other = ClientCAExample::generate(config).expect("unable to generate CA");
+
+    // the serial is revoked, but the certificate isn't ours to vouch for
+    assert_eq!(
+        CertStatus::Unknown,
+        ca.cert_status(&other.client_cert, &serials(&[4242]))
+    );
+    // nor is the issuing CA's own certificate, which the root issued
+    assert_eq!(
+        CertStatus::Unknown,
+        ca.cert_status(&ca.intermediates[0].1, &[])
+    );
+}
+
+#[test]
+fn test_fingerprints() {
+    let fingerprints = |ca: &ClientCAExample| {
+        [
+            ca.root_fingerprint_sha256().unwrap(),
+            ca.intermediate_fingerprint_sha256().unwrap(),
+            ca.client_fingerprint_sha256().unwrap(),
+        ]
+    };
+
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+    let first = fingerprints(&ca);
+
+    for fingerprint in first.iter() {
+        assert_eq!(64, fingerprint.len());
+        assert!(fingerprint
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('A'..='F').contains(&c)));
+    }
+
+    let regenerated = ClientCAExample::generate(ClientCAConfig::default()).unwrap();
+
+    for (a, b) in first.iter().zip(fingerprints(&regenerated).iter()) {
+        assert_ne!(a, b);
+    }
+}
+
+#[test]
+fn test_config_builder_defaults() {
+    let config = ClientCAConfig::builder()
+        .client_subject("Custom Client")
+        .build();
+
+    assert_eq!(
+        DistinguishedName::common_name("Custom Client"),
+        config.client_config.subject
+    );
+
+    // everything else retains the defaults
+    let defaults = ClientCAConfig::default();
+
+    assert_eq!(defaults.root_config.subject, config.root_config.subject);
+    assert_eq!(ROOT_EXPIRY_DURATION, config.root_config.validity);
+    assert_eq!(KeyAlgorithm::default(), config.root_config.key_algo);
+    assert_eq!(1, config.intermediate_configs.len());
+    assert_eq!(
+        INTERMEDIATE_EXPIRY_DURATION,
+        config.intermediate_configs[0].validity
+    );
+    assert_eq!(CLIENT_EXPIRY_DURATION, config.client_config.validity);
+    assert_eq!(KeyAlgorithm::default(), config.client_config.key_algo);
+    assert!(config.client_config.san.is_empty());
+
+    let ca = ClientCAExample::generate(config).expect("unable to generate CA");
+    let cn = ca
+        .client_cert
+        .subject_name()
+        .entries_by_nid(Nid::COMMONNAME)
+        .next()
+        .unwrap();
+
+    assert_eq!("Custom Client", cn.data().to_string().unwrap());
+}
+
+#[test]
+fn test_config_builder_overrides() {
+    let config = ClientCAConfig::builder()
+        .intermediate_validity(Duration::from_secs(60))
+        .key_algo(KeyAlgorithm::Prime256v1)
+        .intermediate_layers(2)
+        .build();
+
+    assert_eq!(KeyAlgorithm::Prime256v1, config.root_config.key_algo);
+    assert_eq!(KeyAlgorithm::Prime256v1, config.client_config.key_algo);
+    assert_eq!(2, config.intermediate_configs.len());
+
+    // settings apply to every layer regardless of the order they were given in
+    for intermediate in config.intermediate_configs.iter() {
+        assert_eq!(KeyAlgorithm::Prime256v1, intermediate.key_algo);
+        assert_eq!(Duration::from_secs(60), intermediate.validity);
+    }
+}
+
+#[test]
+fn test_pinned_validity_window() {
+    let not_before = UNIX_EPOCH.add(Duration::from_secs(1_700_000_000));
+    let not_after = not_before.add(Duration::from_secs(60 * 60 * 24));
+
+    let mut config = ClientCAConfig::default();
+    config.root_config.not_before = Some(not_before);
+    config.root_config.not_after = Some(not_after);
+    config.client_config.not_before = Some(not_before);
+    config.client_config.not_after = Some(not_after);
+
+    let ca = ClientCAExample::generate(config).expect("unable to generate CA");
+
+    let (expected_before, expected_after) = (
+        Asn1Time::from_unix(1_700_000_000).unwrap(),
+        Asn1Time::from_unix(1_700_000_000 + 60 * 60 * 24).unwrap(),
+    );
+
+    for cert in [&ca.root_cert, &ca.client_cert] {
+        assert!(
+            cert.not_before() == expected_before,
+            "{}",
+            cert.not_before()
+        );
+        assert!(cert.not_after() == expected_after, "{}", cert.not_after());
+    }
+
+    // an unpinned layer still derives its window from now
+    assert!(ca.intermediates[0].1.not_after() > Asn1Time::days_from_now(0).unwrap());
+}
+
+#[test]
+fn test_client_key_usage() {
+    let text = |ca: &ClientCAExample| String::from_utf8(ca.client_cert.to_text().unwrap()).unwrap();
+
+    // by default, key usage is omitted entirely
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+    assert!(!text(&ca).contains("X509v3 Key Usage"));
+
+    let mut config = ClientCAConfig::default();
+    config.client_config.key_usage = Some(KeyUsageSpec {
+        digital_signature: true,
+        ..Default::default()
+    });
+    config.client_config.extended_key_usage = ExtendedKeyUsageSpec {
+        email_protection: true,
+        ..Default::default()
+    };
+
+    let ca = ClientCAExample::generate(config).expect("unable to generate CA");
+    let text = text(&ca);
+
+    assert!(
+        text.contains("X509v3 Key Usage: critical\n                Digital Signature\n"),
+        "{text}"
+    );
+    assert!(
+        text.contains("TLS Web Client Authentication, E-mail Protection"),
+        "{text}"
+    );
+
+    ca.verify().expect("unable to verify CA");
+}
+
+#[test]
+fn test_rsa_root_exponent() {
+    let mut config = ClientCAConfig::default();
+    config.root_config.key_algo = KeyAlgorithm::Rsa {
+        bits: 2048,
+        exponent: 3,
+    };
+    config.intermediate_configs[0].key_algo = KeyAlgorithm::Prime256v1;
+
+    let ca = ClientCAExample::generate(config).expect("unable to generate CA");
+    let rsa = ca.root_key.rsa().unwrap();
+
+    assert_eq!(2048, rsa.size() * 8);
+    assert_eq!(3, rsa.e().to_dec_str().unwrap().parse::<u32>().unwrap());
+    // RSA signatures are made over a SHA-256 digest
+    assert_eq!(
+        Nid::SHA256WITHRSAENCRYPTION,
+        ca.intermediates[0].1.signature_algorithm().object().nid()
+    );
+    // leaves remain EdDSA
+    assert_eq!(Id::ED25519, ca.client_key.id());
+
+    ca.verify().expect("unable to verify CA");
+}
+
+#[test]
+fn test_key_identifiers() {
+    let mut config = ClientCAConfig::default();
+    config.intermediate_configs.push(CAConfig::intermediate());
+
+    let ca = ClientCAExample::generate(config).expect("unable to generate CA");
+    let server = ca
+        .issue_server_cert(&ServerCertConfig::default())
+        .expect("unable to issue server cert")
+        .1;
+
+    let ski = |cert: &X509| cert.subject_key_id().expect("no SKI").as_slice().to_vec();
+    let aki = |cert: &X509| cert.authority_key_id().expect("no AKI").as_slice().to_vec();
+
+    assert_eq!(ski(&ca.root_cert), aki(&ca.intermediates[0].1));
+    assert_eq!(ski(&ca.intermediates[0].1), aki(&ca.intermediates[1].1));
+    assert_eq!(ski(&ca.intermediates[1].1), aki(&ca.client_cert));
+    assert_eq!(ski(&ca.intermediates[1].1), aki(&server));
+    // every certificate identifies its own key
+    assert_ne!(ski(&ca.client_cert), ski(&server));
+
+    ca.verify().expect("unable to verify CA");
+    ca.verify_chain().expect("unable to verify chain");
+}
+
+#[test]
+fn test_describe_cert() {
+    let ca = ClientCAExample::generate(
+        ClientCAConfig::builder()
+            .client_subject("Described Client")
+            .build(),
+    )
+    .expect("unable to generate CA");
+
+    let description = describe_cert(&ca.client_cert).expect("unable to describe cert");
+    let serial = ca
+        .client_cert
+        .serial_number()
+        .to_bn()
+        .unwrap()
+        .to_hex_str()
+        .unwrap()
+        .to_string();
+
+    assert!(
+        description.contains("Subject: CN=Described Client\n"),
+        "{description}"
+    );
+    assert!(
+        description.contains(&format!("Serial: {serial}\n")),
+        "{description}"
+    );
+    assert!(description.contains("Public Key: ED25519"), "{description}");
+
+    // the client is issued by the intermediate, which has the default organization
+    let issuer = describe_cert(ca.issuer().1).unwrap();
+    let issuer_subject = issuer
+        .lines()
+        .next()
+        .unwrap()
+        .strip_prefix("Subject: ")
+        .unwrap();
+
+    assert!(description.contains(&format!("Issuer: {issuer_subject}\n")));
+    assert!(
+        issuer_subject.contains(X509_ORGANIZATIONAL_UNIT_NAME),
+        "{issuer}"
+    );
+}
+
+#[test]
+fn test_generate_seeded() {
+    let not_before = UNIX_EPOCH.add(Duration::from_secs(1_700_000_000));
+    let not_after = not_before.add(Duration::from_secs(60 * 60 * 24));
+
+    let config = || {
+        let mut config = ClientCAConfig::default();
+
+        for ca_config in
+            std::iter::once(&mut config.root_config).chain(config.intermediate_configs.iter_mut())
+        {
+            ca_config.not_before = Some(not_before);
+            ca_config.not_after = Some(not_after);
+        }
+
+        config.client_config.not_before = Some(not_before);
+        config.client_config.not_after = Some(not_after);
+        config
+    };
+
+    let der = |ca: &ClientCAExample| {
+        [&ca.root_cert, ca.issuer().1, &ca.client_cert].map(|cert| cert.to_der().unwrap())
+    };
+
+    let first = ClientCAExample::generate_seeded(config(), [7; 32]).expect("unable to generate CA");
+    let second = ClientCAExample::generate_seeded(config(), [7; 32]).unwrap();
+
+    assert!(first.verify().is_ok());
+    assert_eq!(der(&first), der(&second));
+    // the ChaCha20 keystream is fixed, so seeded output is stable across releases
+    assert_eq!(
+        BigNum::from_u32(2_022_834_420).unwrap(),
+        first.root_cert.serial_number().to_bn().unwrap()
+    );
+
+    // a different seed yields different keys and serials
+    let other = ClientCAExample::generate_seeded(config(), [8; 32]).unwrap();
+
+    for (a, b) in der(&first).iter().zip(der(&other).iter()) {
+        assert_ne!(a, b);
+    }
+
+    // non-deterministic algorithms are rejected
+    let mut rsa = config();
+    rsa.client_config.key_algo = KeyAlgorithm::rsa(2048);
+
+    assert!(matches!(
+        ClientCAExample::generate_seeded(rsa, [7; 32]),
+        Err(CaError::Unseedable(KeyAlgorithm::Rsa { bits: 2048, .. }))
+    ));
+}
+
+#[test]
+fn test_verify_against_ca() {
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+    let (_, intermediate) = ca.issuer();
+
+    verify_against_ca(
+        &ca.client_cert,
+        &[ca.root_cert.clone(), intermediate.clone()],
+    )
+    .expect("client cert should be trusted by its own CA");
+
+    // without the intermediate, the chain can't be built up to the root
+    assert!(matches!(
+        verify_against_ca(&ca.client_cert, std::slice::from_ref(&ca.root_cert)),
+        Err(CaError::Untrusted { .. })
+    ));
+
+    // an unrelated root can't vouch for the client cert at all
+    let other =
+        ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+
+    match verify_against_ca(&ca.client_cert, std::slice::from_ref(&other.root_cert)) {
+        Err(CaError::Untrusted { depth, reason }) => {
+            assert_eq!(0, depth);
+            assert_ne!(X509VerifyResult::OK, reason);
+        }
+        other => panic!("expected an untrusted error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_generate_parallel_rsa() {
+    let config = || {
+        let mut config = ClientCAConfig::builder()
+            .key_algo(KeyAlgorithm::rsa(2048))
+            .intermediate_layers(2)
+            .build();
+        config.client_config.key_algo = KeyAlgorithm::Prime256v1;
+        config
+    };
+
+    let parallel = ClientCAExample::generate(config()).expect("unable to generate CA");
+
+    assert_eq!(Id::RSA, parallel.root_key.id());
+    assert_eq!(Id::RSA, parallel.intermediates[1].0.id());
+    assert_eq!(Id::EC, parallel.client_key.id());
+    parallel.verify().expect("unable to verify CA");
+    parallel.verify_chain().expect("unable to verify chain");
+
+    // every layer must have gotten its own key
+    assert!(!parallel
+        .root_key
+        .public_eq(parallel.intermediates[0].0.as_ref()));
+    assert!(!parallel.intermediates[0]
+        .0
+        .public_eq(parallel.intermediates[1].0.as_ref()));
+
+    let sequential =
+        ClientCAExample::generate_sequential(config()).expect("unable to generate CA sequentially");
+
+    sequential.verify().expect("unable to verify CA");
+}
+
+#[test]
+fn test_split_pem_chain() {
+    let ca = ClientCAExample::generate(ClientCAConfig::default()).expect("unable to generate CA");
+    let (_, intermediate) = ca.issuer();
+    let expected = [&ca.root_cert, intermediate, &ca.client_cert];
+
+    let der = |cert: &X509| cert.to_der().unwrap();
+
+    let certs = split_pem_chain(&ca.to_pem_chain().unwrap()).expect("unable to split chain");
+
+    assert_eq!(3, certs.len());
+
+    for (cert, expected) in certs.iter().zip(expected) {
+        assert_eq!(der(expected), der(cert));
+    }
+
+    // joined with CRLF line endings, as written by the example on windows
+    let crlf = expected
+        .iter()
+        .map(|cert| String::from_utf8(cert.to_pem().unwrap()).unwrap())
+        .map(|pem| pem.replace('\n', "\r\n"))
+        .collect::<Vec<_>>()
+        .join("\r\n");
+
+    let certs = split_pem_chain(crlf.as_bytes()).expect("unable to split CRLF chain");
+
+    assert_eq!(3, certs.len());
+
+    for (cert, expected) in certs.iter().zip(expected) {
+        assert_eq!(der(expected), der(cert));
+    }
+}
+
+#[test]
+fn test_is_currently_valid() {
+    let now = SystemTime::now();
+    let hour = Duration::from_secs(60 * 60);
+    let skew = Duration::from_secs(60 * 30);
+
+    // a client cert valid between the given bounds
+    let client_cert = |not_before: Option<SystemTime>, not_after: Option<SystemTime>| {
+        let mut config = ClientCAConfig::default();
+        config.client_config.not_before = not_before;
+        config.client_config.not_after = not_after;
+
+        ClientCAExample::generate(config)
+            .expect("unable to generate CA")
+            .client_cert
+    };
+
+    // the default window covers now
+    assert!(is_currently_valid(&client_cert(None, None), skew));
+
+    // expired yesterday
+    let expired = client_cert(Some(now.sub(48 * hour)), Some(now.sub(24 * hour)));
+    assert!(!is_currently_valid(&expired, skew));
+
+    // not valid for another two hours, well beyond the skew
+    let future = client_cert(Some(now.add(2 * hour)), Some(now.add(3 * hour)));
+    assert!(!is_currently_valid(&future, skew));
+
+    // only a few minutes out either way, within the skew
+    let starting = client_cert(Some(now.add(skew / 2)), Some(now.add(hour)));
+    let ending = client_cert(Some(now.sub(hour)), Some(now.sub(skew / 2)));
+
+    assert!(is_currently_valid(&starting, skew));
+    assert!(is_currently_valid(&ending, skew));
+    assert!(!is_currently_valid(&starting, Duration::ZERO));
+    assert!(!is_currently_valid(&ending, Duration::ZERO));
+}
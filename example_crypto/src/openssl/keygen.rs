@@ -1,5 +1,11 @@
+#[cfg(test)]
+mod tests;
+
+use openssl::derive::Deriver;
 use openssl::ec::{EcGroup, EcKey};
-use openssl::pkey::PKey;
+use openssl::error::ErrorStack;
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private, Public};
 use openssl::rsa::Rsa;
 
 pub struct KeyPair {
@@ -7,56 +13,179 @@ pub struct KeyPair {
     pub public: Vec<u8>,
 }
 
-pub fn keygen_ec(group: &EcGroup) -> KeyPair {
-    let private = EcKey::generate(group).unwrap();
-
-    KeyPair {
-        private: private.private_key().to_vec(),
-        public: private.public_key_to_der().unwrap(),
+impl KeyPair {
+    /// Serialize the raw private and public key bytes of an EdDSA or ECDH key.
+    ///
+    /// This is how the byte-returning `keygen_*` functions serialize their `*_pkey` counterparts.
+    pub fn from_raw(pk: &PKey<Private>) -> Result<Self, ErrorStack> {
+        Ok(Self {
+            private: pk.raw_private_key()?,
+            public: pk.raw_public_key()?,
+        })
     }
 }
 
-pub fn keygen_ed25519() -> KeyPair {
-    let pk = PKey::generate_ed25519().unwrap();
+/// A keypair serialized as PEM: the private key as PKCS#8 and the public key as SubjectPublicKeyInfo.
+pub struct KeyPairPem {
+    pub private_pem: Vec<u8>,
+    pub public_pem: Vec<u8>,
+}
 
-    KeyPair {
-        private: pk.raw_private_key().unwrap(),
-        public: pk.raw_public_key().unwrap(),
+impl KeyPairPem {
+    /// Serialize a private key and its public half as PEM.
+    pub fn from_pkey(pk: &PKey<Private>) -> Result<Self, ErrorStack> {
+        Ok(Self {
+            private_pem: pk.private_key_to_pem_pkcs8()?,
+            public_pem: pk.public_key_to_pem()?,
+        })
     }
 }
 
-pub fn keygen_ed448() -> KeyPair {
-    let pk = PKey::generate_ed448().unwrap();
+impl TryFrom<&PKey<Private>> for KeyPairPem {
+    type Error = ErrorStack;
 
-    KeyPair {
-        private: pk.raw_private_key().unwrap(),
-        public: pk.raw_public_key().unwrap(),
+    fn try_from(pk: &PKey<Private>) -> Result<Self, Self::Error> {
+        Self::from_pkey(pk)
     }
 }
 
-pub fn keygen_x25519() -> KeyPair {
-    let pk = PKey::generate_x25519().unwrap();
+pub fn keygen_ec_pkey(group: &EcGroup) -> Result<PKey<Private>, ErrorStack> {
+    PKey::from_ec_key(EcKey::generate(group)?)
+}
 
-    KeyPair {
-        private: pk.raw_private_key().unwrap(),
-        public: pk.raw_public_key().unwrap(),
-    }
+pub fn keygen_ec(group: &EcGroup) -> Result<KeyPair, ErrorStack> {
+    let private = keygen_ec_pkey(group)?.ec_key()?;
+
+    Ok(KeyPair {
+        private: private.private_key().to_vec(),
+        public: private.public_key_to_der()?,
+    })
 }
 
-pub fn keygen_x448() -> KeyPair {
-    let pk = PKey::generate_x448().unwrap();
+/// Generate a key on a named curve, failing if the curve is unavailable in this OpenSSL build.
+fn keygen_named_curve_pkey(nid: Nid) -> Result<PKey<Private>, ErrorStack> {
+    keygen_ec_pkey(&EcGroup::from_curve_name(nid)?)
+}
 
-    KeyPair {
-        private: pk.raw_private_key().unwrap(),
-        public: pk.raw_public_key().unwrap(),
-    }
+/// Generate a key on a named curve and serialize it as [keygen_ec] does.
+fn keygen_named_curve(nid: Nid) -> Result<KeyPair, ErrorStack> {
+    keygen_ec(&EcGroup::from_curve_name(nid)?)
 }
 
-pub fn keygen_rsa(bits: u32) -> KeyPair {
-    let rsa = Rsa::generate(bits).unwrap();
+pub fn keygen_secp521r1_pkey() -> Result<PKey<Private>, ErrorStack> {
+    keygen_named_curve_pkey(Nid::SECP521R1)
+}
 
-    KeyPair {
-        private: rsa.private_key_to_der().unwrap(),
-        public: rsa.public_key_to_der().unwrap(),
-    }
+pub fn keygen_secp521r1() -> Result<KeyPair, ErrorStack> {
+    keygen_named_curve(Nid::SECP521R1)
+}
+
+pub fn keygen_brainpool_p256r1_pkey() -> Result<PKey<Private>, ErrorStack> {
+    keygen_named_curve_pkey(Nid::BRAINPOOL_P256R1)
+}
+
+pub fn keygen_brainpool_p256r1() -> Result<KeyPair, ErrorStack> {
+    keygen_named_curve(Nid::BRAINPOOL_P256R1)
+}
+
+pub fn keygen_brainpool_p384r1_pkey() -> Result<PKey<Private>, ErrorStack> {
+    keygen_named_curve_pkey(Nid::BRAINPOOL_P384R1)
+}
+
+pub fn keygen_brainpool_p384r1() -> Result<KeyPair, ErrorStack> {
+    keygen_named_curve(Nid::BRAINPOOL_P384R1)
+}
+
+pub fn keygen_brainpool_p512r1_pkey() -> Result<PKey<Private>, ErrorStack> {
+    keygen_named_curve_pkey(Nid::BRAINPOOL_P512R1)
+}
+
+pub fn keygen_brainpool_p512r1() -> Result<KeyPair, ErrorStack> {
+    keygen_named_curve(Nid::BRAINPOOL_P512R1)
+}
+
+pub fn keygen_ed25519_pkey() -> Result<PKey<Private>, ErrorStack> {
+    PKey::generate_ed25519()
+}
+
+pub fn keygen_ed25519() -> Result<KeyPair, ErrorStack> {
+    KeyPair::from_raw(&keygen_ed25519_pkey()?)
+}
+
+pub fn keygen_ed448_pkey() -> Result<PKey<Private>, ErrorStack> {
+    PKey::generate_ed448()
+}
+
+pub fn keygen_ed448() -> Result<KeyPair, ErrorStack> {
+    KeyPair::from_raw(&keygen_ed448_pkey()?)
+}
+
+pub fn keygen_x25519_pkey() -> Result<PKey<Private>, ErrorStack> {
+    PKey::generate_x25519()
+}
+
+pub fn keygen_x25519() -> Result<KeyPair, ErrorStack> {
+    KeyPair::from_raw(&keygen_x25519_pkey()?)
+}
+
+pub fn keygen_x448_pkey() -> Result<PKey<Private>, ErrorStack> {
+    PKey::generate_x448()
+}
+
+pub fn keygen_x448() -> Result<KeyPair, ErrorStack> {
+    KeyPair::from_raw(&keygen_x448_pkey()?)
+}
+
+/// Derive the shared secret between our private key and the peer's public key, e.g. for X25519 or
+/// X448 key agreement.
+///
+/// Both parties derive the same secret from their own private key and the other's public key,
+/// without the secret itself ever being exchanged.
+pub fn derive_shared_secret(
+    private: &PKey<Private>,
+    peer_public: &PKey<Public>,
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut deriver = Deriver::new(private)?;
+    deriver.set_peer(peer_public)?;
+    deriver.derive_to_vec()
+}
+
+/// Demonstrate X25519 key agreement between two freshly generated parties, returning the secret
+/// derived by each side; these are always equal.
+pub fn x25519_key_agreement() -> Result<(Vec<u8>, Vec<u8>), ErrorStack> {
+    key_agreement(keygen_x25519_pkey)
+}
+
+/// Demonstrate X448 key agreement as in [x25519_key_agreement].
+pub fn x448_key_agreement() -> Result<(Vec<u8>, Vec<u8>), ErrorStack> {
+    key_agreement(keygen_x448_pkey)
+}
+
+fn key_agreement(
+    keygen: fn() -> Result<PKey<Private>, ErrorStack>,
+) -> Result<(Vec<u8>, Vec<u8>), ErrorStack> {
+    let (alice, bob) = (keygen()?, keygen()?);
+
+    // only the public halves are exchanged between the parties
+    let public =
+        |pk: &PKey<Private>| PKey::public_key_from_raw_bytes(&pk.raw_public_key()?, pk.id());
+    let (alice_public, bob_public) = (public(&alice)?, public(&bob)?);
+
+    Ok((
+        derive_shared_secret(&alice, &bob_public)?,
+        derive_shared_secret(&bob, &alice_public)?,
+    ))
+}
+
+pub fn keygen_rsa_pkey(bits: u32) -> Result<PKey<Private>, ErrorStack> {
+    PKey::from_rsa(Rsa::generate(bits)?)
+}
+
+pub fn keygen_rsa(bits: u32) -> Result<KeyPair, ErrorStack> {
+    let rsa = keygen_rsa_pkey(bits)?.rsa()?;
+
+    Ok(KeyPair {
+        private: rsa.private_key_to_der()?,
+        public: rsa.public_key_to_der()?,
+    })
 }
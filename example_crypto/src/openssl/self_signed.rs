@@ -0,0 +1,148 @@
+//! Generation of a standalone self-signed CA certificate, such as a throwaway certificate for an
+//! example or test which doesn't need a full [ClientCAExample](super::client_ca::ClientCAExample).
+
+#[cfg(test)]
+mod tests;
+
+use crate::openssl::client_ca::{
+    ClientCAExample, DistinguishedName, KeyAlgorithm, NOT_BEFORE_DRIFT_DURATION, X509_CERT_VERSION,
+};
+
+use openssl::asn1::Asn1Integer;
+use openssl::bn::BigNum;
+use openssl::error::ErrorStack;
+use openssl::pkey::{PKey, Private};
+use openssl::x509::extension::{BasicConstraints, KeyUsage};
+use openssl::x509::{X509Builder, X509};
+use std::time::Duration;
+
+/// The common name used for self-signed certificates unless otherwise configured.
+pub const SELF_SIGNED_SUBJECT_NAME: &str = "Temporary OpenSSL Cert";
+
+/// The certificate expiry duration for self-signed certificates.
+///
+/// Set to 12 hours.
+pub const SELF_SIGNED_EXPIRY_DURATION: Duration = Duration::from_secs(60 * 60 * 12);
+
+/// The serial number used for self-signed certificates unless otherwise configured.
+pub const SELF_SIGNED_SERIAL_NUMBER: u32 = 1000;
+
+/// Builds a self-signed CA certificate permitting a single layer of intermediates beneath it,
+/// along with its private key.
+pub struct SelfSignedBuilder {
+    subject: DistinguishedName,
+    key: Option<PKey<Private>>,
+    key_algo: KeyAlgorithm,
+    max_drift: Duration,
+    validity: Duration,
+    serial_number: u32,
+}
+
+impl Default for SelfSignedBuilder {
+    fn default() -> Self {
+        Self {
+            subject: DistinguishedName::with_default_org(SELF_SIGNED_SUBJECT_NAME),
+            key: None,
+            key_algo: KeyAlgorithm::default(),
+            max_drift: NOT_BEFORE_DRIFT_DURATION,
+            validity: SELF_SIGNED_EXPIRY_DURATION,
+            serial_number: SELF_SIGNED_SERIAL_NUMBER,
+        }
+    }
+}
+
+impl SelfSignedBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the subject, which is also the issuer, of the certificate.
+    pub fn subject(mut self, subject: DistinguishedName) -> Self {
+        self.subject = subject;
+        self
+    }
+
+    /// Use an existing private key rather than generating one.
+    pub fn key(mut self, key: PKey<Private>) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Set the algorithm of the generated private key, ignored if a key is given with
+    /// [SelfSignedBuilder::key].
+    pub fn key_algo(mut self, key_algo: KeyAlgorithm) -> Self {
+        self.key_algo = key_algo;
+        self
+    }
+
+    /// Set how long before now the certificate becomes valid, to tolerate clock drift.
+    pub fn max_drift(mut self, max_drift: Duration) -> Self {
+        self.max_drift = max_drift;
+        self
+    }
+
+    /// Set how long from now the certificate remains valid.
+    pub fn validity(mut self, validity: Duration) -> Self {
+        self.validity = validity;
+        self
+    }
+
+    pub fn serial_number(mut self, serial_number: u32) -> Self {
+        self.serial_number = serial_number;
+        self
+    }
+
+    /// Generate the private key and the certificate, signed by that key.
+    pub fn build(self) -> Result<(PKey<Private>, X509), ErrorStack> {
+        let (key, mut builder) = self.builder()?;
+
+        builder.sign(&key, ClientCAExample::signature_digest(&key)?)?;
+
+        Ok((key, builder.build()))
+    }
+
+    /// Generate the private key and the certificate without signing it, leaving a certificate
+    /// which only refers to itself as its issuer.
+    pub fn build_unsigned(self) -> Result<(PKey<Private>, X509), ErrorStack> {
+        let (key, builder) = self.builder()?;
+
+        Ok((key, builder.build()))
+    }
+
+    /// Produce the key and a certificate builder with everything but the signature.
+    fn builder(self) -> Result<(PKey<Private>, X509Builder), ErrorStack> {
+        let key = match self.key {
+            Some(key) => key,
+            None => self.key_algo.generate()?,
+        };
+
+        let subject_name = self.subject.to_x509_name()?;
+
+        let key_usage = KeyUsage::new()
+            .critical()
+            .digital_signature()
+            .key_cert_sign()
+            .build()?;
+
+        let basic_constraints = BasicConstraints::new().critical().ca().pathlen(1).build()?;
+
+        let (not_before, not_after) =
+            ClientCAExample::validity_window(self.max_drift, self.validity, None, None)?;
+
+        let mut builder = X509::builder()?;
+        builder.set_version(X509_CERT_VERSION)?;
+        builder.set_subject_name(&subject_name)?;
+        // self-referential, so the issuer is the subject
+        builder.set_issuer_name(&subject_name)?;
+        builder.set_serial_number(
+            Asn1Integer::from_bn(BigNum::from_u32(self.serial_number)?.as_ref())?.as_ref(),
+        )?;
+        builder.set_not_before(&not_before)?;
+        builder.set_not_after(&not_after)?;
+        builder.set_pubkey(&key)?;
+        builder.append_extension(key_usage)?;
+        builder.append_extension(basic_constraints)?;
+
+        Ok((key, builder))
+    }
+}
@@ -0,0 +1,75 @@
+use super::{SelfSignedBuilder, SELF_SIGNED_SERIAL_NUMBER, SELF_SIGNED_SUBJECT_NAME};
+
+use crate::openssl::client_ca::{DistinguishedName, KeyAlgorithm};
+
+use openssl::asn1::Asn1Time;
+use openssl::nid::Nid;
+use openssl::pkey::{Id, PKey};
+use std::time::Duration;
+
+#[test]
+fn test_self_signed_verifies() {
+    let (key, cert) = SelfSignedBuilder::new()
+        .build()
+        .expect("unable to build cert");
+
+    assert_eq!(Id::ED25519, key.id());
+    assert!(cert.verify(&key).unwrap());
+    assert!(cert.public_key().unwrap().public_eq(&key));
+
+    // issued by itself
+    assert_eq!(
+        cert.subject_name().to_der().unwrap(),
+        cert.issuer_name().to_der().unwrap()
+    );
+
+    let common_name = cert
+        .subject_name()
+        .entries_by_nid(Nid::COMMONNAME)
+        .next()
+        .unwrap()
+        .data()
+        .as_slice()
+        .to_vec();
+
+    assert_eq!(SELF_SIGNED_SUBJECT_NAME.as_bytes(), common_name);
+    assert_eq!(
+        SELF_SIGNED_SERIAL_NUMBER.to_string(),
+        cert.serial_number()
+            .to_bn()
+            .unwrap()
+            .to_dec_str()
+            .unwrap()
+            .to_string()
+    );
+}
+
+#[test]
+fn test_self_signed_options() {
+    let existing = PKey::ec_gen("prime256v1").unwrap();
+
+    let (key, cert) = SelfSignedBuilder::new()
+        .subject(DistinguishedName::common_name("Custom"))
+        .key(existing.clone())
+        // ignored, since a key was given
+        .key_algo(KeyAlgorithm::Ed448)
+        .validity(Duration::from_secs(60))
+        .serial_number(7)
+        .build()
+        .expect("unable to build cert");
+
+    assert!(key.public_eq(&existing));
+    assert!(cert.verify(&existing).unwrap());
+    assert_eq!(1, cert.subject_name().entries().count());
+    assert!(cert.not_after() < Asn1Time::days_from_now(1).unwrap());
+}
+
+#[test]
+fn test_self_signed_unsigned() {
+    let (key, cert) = SelfSignedBuilder::new()
+        .build_unsigned()
+        .expect("unable to build cert");
+
+    assert!(cert.public_key().unwrap().public_eq(&key));
+    assert!(!cert.verify(&key).unwrap_or(false));
+}
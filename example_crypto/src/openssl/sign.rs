@@ -1,7 +1,18 @@
+#[cfg(test)]
+mod tests;
+
+use openssl::hash::MessageDigest;
 use openssl::pkey::{Id, PKey, Private};
-use openssl::sign::Signer;
+use openssl::sign::{Signer, Verifier};
 use rand::{thread_rng, RngCore};
 
+/// The length in bytes of an Ed25519 signature.
+pub const ED25519_SIGNATURE_LENGTH: usize = 64;
+
+/// The length in bytes of an Ed448 signature.
+pub const ED448_SIGNATURE_LENGTH: usize = 114;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum EllipticCurve {
     Ed25519,
     Ed448,
@@ -9,8 +20,170 @@ pub enum EllipticCurve {
     SECP384R1,
 }
 
+impl EllipticCurve {
+    /// Whether this is an Edwards curve, signing with EdDSA rather than ECDSA.
+    pub fn is_edwards(&self) -> bool {
+        matches!(self, Self::Ed25519 | Self::Ed448)
+    }
+
+    /// The length of an EdDSA signature over this curve, or [None] if it is not an Edwards curve.
+    pub fn eddsa_signature_length(&self) -> Option<usize> {
+        match self {
+            Self::Ed25519 => Some(ED25519_SIGNATURE_LENGTH),
+            Self::Ed448 => Some(ED448_SIGNATURE_LENGTH),
+            Self::SECP256R1 | Self::SECP384R1 => None,
+        }
+    }
+}
+
+/// Signs data and verifies signatures, regardless of whether the algorithm uses a digest.
+pub trait DigitalSigner {
+    /// Sign the data, returning the signature.
+    fn sign(&self, data: &[u8]) -> Vec<u8>;
+    /// Verify a signature over the data, returning `false` if the signature is malformed.
+    fn verify(&self, data: &[u8], signature: &[u8]) -> bool;
+}
+
+/// A signature to check with [verify_batch], along with the data it covers and the signer whose
+/// key purportedly produced it.
+pub struct BatchItem<'a> {
+    pub signer: &'a dyn DigitalSigner,
+    pub data: &'a [u8],
+    pub signature: &'a [u8],
+}
+
+/// Verify many signatures, returning whether each one is valid in the same order as `items`.
+///
+/// OpenSSL verification contexts are bound to a single key, so there's no setup to share between
+/// items with different keys; each signature is checked with [DigitalSigner::verify], and an
+/// invalid signature doesn't stop the rest of the batch from being checked.
+pub fn verify_batch(items: &[BatchItem<'_>]) -> Vec<bool> {
+    items
+        .iter()
+        .map(|item| item.signer.verify(item.data, item.signature))
+        .collect()
+}
+
+/// A private key on a known curve, from which a [DigitalSigner] can be constructed.
+pub struct SignVerifyKey {
+    pub curve: EllipticCurve,
+    pub key: PKey<Private>,
+}
+
+impl SignVerifyKey {
+    /// Generate a new random key on the given curve.
+    pub fn generate(curve: EllipticCurve) -> Self {
+        let key = match curve {
+            EllipticCurve::Ed25519 => PKey::generate_ed25519(),
+            EllipticCurve::Ed448 => PKey::generate_ed448(),
+            EllipticCurve::SECP256R1 => PKey::ec_gen("prime256v1"),
+            EllipticCurve::SECP384R1 => PKey::ec_gen("secp384r1"),
+        }
+        .expect("unable to generate private key");
+
+        Self { curve, key }
+    }
+}
+
+/// A signer over either of the EdDSA curves, producing signatures of the curve's length.
+///
+/// EdDSA hashes the message internally, so no digest is used.
 pub struct EdDSASigner {
+    pub curve: EllipticCurve,
+    pub key: PKey<Private>,
+}
+
+impl EdDSASigner {
+    /// Construct a signer from an existing key.
+    ///
+    /// Panics if the key is not on an Edwards curve, as only Ed25519 and Ed448 support EdDSA.
+    pub fn new(key: SignVerifyKey) -> Self {
+        assert!(
+            key.curve.is_edwards(),
+            "{:?} is not an Edwards curve and cannot be used for EdDSA",
+            key.curve
+        );
+
+        Self {
+            curve: key.curve,
+            key: key.key,
+        }
+    }
+
+    /// Generate a new signer with a random key on the given curve.
+    ///
+    /// Panics if the curve is not an Edwards curve, as only Ed25519 and Ed448 support EdDSA.
+    pub fn generate(curve: EllipticCurve) -> Self {
+        assert!(
+            curve.is_edwards(),
+            "{curve:?} is not an Edwards curve and cannot be used for EdDSA"
+        );
+
+        Self::new(SignVerifyKey::generate(curve))
+    }
+
+    /// Sign the data, returning a signature of the curve's signature length.
+    pub fn sign(&self, data: &[u8]) -> Vec<u8> {
+        Signer::new_without_digest(&self.key)
+            .expect("unable to create signer")
+            .sign_oneshot_to_vec(data)
+            .expect("unable to sign data")
+    }
+}
+
+impl DigitalSigner for EdDSASigner {
+    fn sign(&self, data: &[u8]) -> Vec<u8> {
+        EdDSASigner::sign(self, data)
+    }
+
+    fn verify(&self, data: &[u8], signature: &[u8]) -> bool {
+        Verifier::new_without_digest(&self.key)
+            .expect("unable to create verifier")
+            .verify_oneshot(signature, data)
+            .unwrap_or(false)
+    }
+}
+
+/// A signer over the NIST prime curves, hashing the message with the configured digest.
+pub struct EcdsaSigner {
+    pub curve: EllipticCurve,
     pub key: PKey<Private>,
+    pub digest: MessageDigest,
+}
+
+impl EcdsaSigner {
+    /// Construct a signer from an existing key, hashing messages with the given digest.
+    ///
+    /// Panics if the key is on an Edwards curve, which must be used with [EdDSASigner] instead.
+    pub fn new(key: SignVerifyKey, digest: MessageDigest) -> Self {
+        assert!(
+            !key.curve.is_edwards(),
+            "{:?} is an Edwards curve and cannot be used for ECDSA",
+            key.curve
+        );
+
+        Self {
+            curve: key.curve,
+            key: key.key,
+            digest,
+        }
+    }
+}
+
+impl DigitalSigner for EcdsaSigner {
+    fn sign(&self, data: &[u8]) -> Vec<u8> {
+        Signer::new(self.digest, &self.key)
+            .expect("unable to create signer")
+            .sign_oneshot_to_vec(data)
+            .expect("unable to sign data")
+    }
+
+    fn verify(&self, data: &[u8], signature: &[u8]) -> bool {
+        Verifier::new(self.digest, &self.key)
+            .expect("unable to create verifier")
+            .verify_oneshot(signature, data)
+            .unwrap_or(false)
+    }
 }
 
 pub struct Ed25519Signer {
@@ -36,16 +209,41 @@ impl Ed25519Signer {
         Self { key }
     }
 
-    pub fn sign(&self, data: &[u8]) -> [u8; 64] {
-        let mut sig = [0; 64];
+    pub fn sign(&self, data: &[u8]) -> [u8; ED25519_SIGNATURE_LENGTH] {
+        let mut sig = [0; ED25519_SIGNATURE_LENGTH];
 
-        let _signature_length = Signer::new_without_digest(&self.key)
-            .expect("unable to create signer")
+        let mut signer = Signer::new_without_digest(&self.key).expect("unable to create signer");
+
+        // the buffer must be exactly the size the key produces, otherwise signing would fail or
+        // leave trailing zeroes
+        debug_assert_eq!(
+            sig.len(),
+            signer.len().expect("unable to get signature length")
+        );
+
+        let signature_length = signer
             .sign_oneshot(&mut sig, data)
             .expect("unable to sign data");
 
+        assert_eq!(sig.len(), signature_length, "signature was truncated");
+
         sig
     }
+
+    /// Verify a signature over the data against this signer's public key.
+    pub fn verify(&self, data: &[u8], signature: &[u8]) -> bool {
+        Verifier::new_without_digest(&self.key)
+            .expect("unable to create verifier")
+            .verify_oneshot(signature, data)
+            .unwrap_or(false)
+    }
+
+    /// The raw bytes of this signer's public key.
+    pub fn public_key_raw(&self) -> Vec<u8> {
+        self.key
+            .raw_public_key()
+            .expect("unable to extract raw public key")
+    }
 }
 
 pub struct Ed448Signer {
@@ -54,22 +252,45 @@ pub struct Ed448Signer {
 
 impl Ed448Signer {
     /// Generate a new signer with a randomly generated key.
-    ///
-    /// All values are legal in EdDSA keys, so it's simply generating 32 bytes from the CSPNG.
     pub fn random() -> Self {
         Self {
             key: PKey::generate_ed448().unwrap(),
         }
     }
 
-    pub fn sign(&self, data: &[u8]) -> [u8; 64] {
-        let mut sig = [0; 64];
+    pub fn sign(&self, data: &[u8]) -> [u8; ED448_SIGNATURE_LENGTH] {
+        let mut sig = [0; ED448_SIGNATURE_LENGTH];
 
-        let _signature_length = Signer::new_without_digest(&self.key)
-            .expect("unable to create signer")
+        let mut signer = Signer::new_without_digest(&self.key).expect("unable to create signer");
+
+        // the buffer must be exactly the size the key produces, otherwise signing would fail or
+        // leave trailing zeroes
+        debug_assert_eq!(
+            sig.len(),
+            signer.len().expect("unable to get signature length")
+        );
+
+        let signature_length = signer
             .sign_oneshot(&mut sig, data)
             .expect("unable to sign data");
 
+        assert_eq!(sig.len(), signature_length, "signature was truncated");
+
         sig
     }
+
+    /// Verify a signature over the data against this signer's public key.
+    pub fn verify(&self, data: &[u8], signature: &[u8]) -> bool {
+        Verifier::new_without_digest(&self.key)
+            .expect("unable to create verifier")
+            .verify_oneshot(signature, data)
+            .unwrap_or(false)
+    }
+
+    /// The raw bytes of this signer's public key.
+    pub fn public_key_raw(&self) -> Vec<u8> {
+        self.key
+            .raw_public_key()
+            .expect("unable to extract raw public key")
+    }
 }
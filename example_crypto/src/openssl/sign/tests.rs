@@ -0,0 +1,179 @@
+use super::{
+    verify_batch, BatchItem, DigitalSigner, EcdsaSigner, Ed25519Signer, Ed448Signer, EdDSASigner,
+    EllipticCurve, SignVerifyKey, ED25519_SIGNATURE_LENGTH, ED448_SIGNATURE_LENGTH,
+};
+
+use openssl::hash::MessageDigest;
+use openssl::pkey::{HasPublic, PKeyRef};
+use openssl::sign::{Signer, Verifier};
+
+const MESSAGE: &[u8] = b"the quick brown fox jumps over the lazy dog";
+
+fn verifies<T: HasPublic>(key: &PKeyRef<T>, signature: &[u8]) -> bool {
+    Verifier::new_without_digest(key)
+        .expect("unable to create verifier")
+        .verify_oneshot(signature, MESSAGE)
+        .expect("unable to verify signature")
+}
+
+#[test]
+fn test_eddsa_ed25519() {
+    let signer = EdDSASigner::generate(EllipticCurve::Ed25519);
+    let signature = signer.sign(MESSAGE);
+
+    assert_eq!(ED25519_SIGNATURE_LENGTH, signature.len());
+    assert!(verifies(&signer.key, &signature));
+}
+
+#[test]
+fn test_eddsa_ed448() {
+    let signer = EdDSASigner::generate(EllipticCurve::Ed448);
+    let signature = signer.sign(MESSAGE);
+
+    assert_eq!(ED448_SIGNATURE_LENGTH, signature.len());
+    assert!(verifies(&signer.key, &signature));
+}
+
+#[test]
+#[should_panic(expected = "not an Edwards curve")]
+fn test_eddsa_rejects_weierstrass_curve() {
+    EdDSASigner::generate(EllipticCurve::SECP256R1);
+}
+
+#[test]
+fn test_ed448_signer() {
+    let signer = Ed448Signer::random();
+    let signature = signer.sign(MESSAGE);
+
+    assert_eq!(ED448_SIGNATURE_LENGTH, signature.len());
+    assert!(signer.verify(MESSAGE, &signature));
+}
+
+#[test]
+fn test_ed25519_signer_round_trip() {
+    let signer = Ed25519Signer::random();
+    let signature = signer.sign(MESSAGE);
+
+    assert!(signer.verify(MESSAGE, &signature));
+    assert!(!signer.verify(b"the quick brown fox jumps over the lazy cat", &signature));
+    assert_eq!(32, signer.public_key_raw().len());
+}
+
+#[test]
+fn test_ed448_signer_round_trip() {
+    let signer = Ed448Signer::random();
+    let signature = signer.sign(MESSAGE);
+
+    assert!(signer.verify(MESSAGE, &signature));
+    assert!(!signer.verify(b"the quick brown fox jumps over the lazy cat", &signature));
+    assert_eq!(57, signer.public_key_raw().len());
+}
+
+#[test]
+fn test_ed448_signer_signature_size() {
+    let signer = Ed448Signer::random();
+    let signature = signer.sign(MESSAGE);
+
+    let expected = Signer::new_without_digest(&signer.key)
+        .expect("unable to create signer")
+        .len()
+        .expect("unable to get signature length");
+
+    assert_eq!(expected, signature.len());
+    assert!(verifies(&signer.key, &signature));
+}
+
+fn round_trip(signer: &dyn DigitalSigner) {
+    let signature = signer.sign(MESSAGE);
+
+    assert!(signer.verify(MESSAGE, &signature));
+    assert!(!signer.verify(b"the quick brown fox jumps over the lazy cat", &signature));
+}
+
+#[test]
+fn test_digital_signer_eddsa() {
+    round_trip(&EdDSASigner::new(SignVerifyKey::generate(
+        EllipticCurve::Ed25519,
+    )));
+}
+
+#[test]
+fn test_digital_signer_ecdsa() {
+    round_trip(&EcdsaSigner::new(
+        SignVerifyKey::generate(EllipticCurve::SECP384R1),
+        MessageDigest::sha384(),
+    ));
+}
+
+#[test]
+#[should_panic(expected = "is an Edwards curve")]
+fn test_ecdsa_rejects_edwards_curve() {
+    EcdsaSigner::new(
+        SignVerifyKey::generate(EllipticCurve::Ed25519),
+        MessageDigest::sha256(),
+    );
+}
+
+#[test]
+fn test_verify_batch() {
+    let eddsa = EdDSASigner::generate(EllipticCurve::Ed448);
+    let ecdsa = EcdsaSigner::new(
+        SignVerifyKey::generate(EllipticCurve::SECP256R1),
+        MessageDigest::sha256(),
+    );
+
+    let eddsa_signature = eddsa.sign(MESSAGE);
+    let ecdsa_signature = ecdsa.sign(MESSAGE);
+
+    let mut corrupted = eddsa_signature.clone();
+    corrupted[0] ^= 0x01;
+
+    // flipping the first byte breaks the DER SEQUENCE tag, so the signature can't even be decoded
+    let mut malformed = ecdsa_signature.clone();
+    malformed[0] ^= 0x01;
+
+    let items = [
+        BatchItem {
+            signer: &eddsa,
+            data: MESSAGE,
+            signature: &eddsa_signature,
+        },
+        BatchItem {
+            signer: &eddsa,
+            data: MESSAGE,
+            signature: &corrupted,
+        },
+        BatchItem {
+            signer: &ecdsa,
+            data: MESSAGE,
+            signature: &ecdsa_signature,
+        },
+        // a valid signature over different data
+        BatchItem {
+            signer: &ecdsa,
+            data: b"the quick brown fox jumps over the lazy cat",
+            signature: &ecdsa_signature,
+        },
+        // a valid signature checked against the wrong key
+        BatchItem {
+            signer: &ecdsa,
+            data: MESSAGE,
+            signature: &EcdsaSigner::new(
+                SignVerifyKey::generate(EllipticCurve::SECP256R1),
+                MessageDigest::sha256(),
+            )
+            .sign(MESSAGE),
+        },
+        BatchItem {
+            signer: &ecdsa,
+            data: MESSAGE,
+            signature: &malformed,
+        },
+    ];
+
+    assert_eq!(
+        vec![true, false, true, false, false, false],
+        verify_batch(&items)
+    );
+    assert!(verify_batch(&[]).is_empty());
+}
@@ -1,17 +1,33 @@
+#[cfg(feature = "tokio")]
+mod rand_async;
 mod rand_crate;
 mod rand_openssl;
+#[cfg(feature = "rayon")]
+mod rand_parallel;
 mod rand_sys;
 
 #[cfg(test)]
 mod tests;
 
+use base64::prelude::BASE64_URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
 use rand::rngs::{OsRng, ThreadRng};
-use rand::Rng;
+use rand::{CryptoRng, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::{self, BufReader, ErrorKind, Read, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::Duration;
 
+#[cfg(feature = "tokio")]
+pub use rand_async::AsyncRandGenerator;
 pub use rand_crate::SecureOsGenerator as RandCrateOs;
 pub use rand_crate::SecureThreadGenerator as RandCrateThread;
+#[cfg(feature = "rayon")]
+pub use rand_parallel::{ParallelRandGenerator, PARALLEL_CHUNK_SIZE};
 pub use rand_sys::SysRandomBufferedGenerator as SysRandomBuffered;
 pub use rand_sys::SysRandomDirectGenerator as SysRandomDirect;
 pub use rand_sys::SysUrandomBufferedGenerator as SysUrandomBuffered;
@@ -22,45 +38,711 @@ pub use rand_sys::SysUrandomDirectGenerator as SysUrandomDirect;
 pub trait RandGenerator {
     /// Fill a mutable buffer of bytes with random data.
     fn fill(&mut self, bytes: &mut [u8]);
+    /// Whether this generator is suitable for cryptographic use, so that callers can assert at
+    /// runtime that they're not accidentally using a deterministic test source.
+    ///
+    /// Generators reading from an arbitrary source, such as [ReadRng], trust that source.
+    fn is_cryptographically_secure(&self) -> bool {
+        true
+    }
     /// Create, fill, and return a fixed-size array on the stack.
-    fn generate_array<const S: usize>(&mut self) -> [u8; S] {
+    fn generate_array<const S: usize>(&mut self) -> [u8; S]
+    where
+        Self: Sized,
+    {
         let mut arr = [0; S];
         self.fill(&mut arr);
         arr
     }
     /// Create, fill, and return a fixed-size vector on the heap.
-    fn generate_vec<const S: usize>(&mut self) -> Vec<u8> {
+    fn generate_vec<const S: usize>(&mut self) -> Vec<u8>
+    where
+        Self: Sized,
+    {
         let mut buf = vec![0; S];
         self.fill(buf.as_mut_slice());
         buf
     }
+    /// Create, fill, and return `count` fixed-size arrays.
+    ///
+    /// By default, all arrays are filled with a single call to [RandGenerator::fill], amortizing
+    /// per-call overhead across the batch.
+    fn generate_many<const S: usize>(&mut self, count: usize) -> Vec<[u8; S]>
+    where
+        Self: Sized,
+    {
+        let mut arrays = vec![[0; S]; count];
+        self.fill(arrays.as_flattened_mut());
+        arrays
+    }
+    /// Generate `len` random bytes and return them as a lowercase hex string.
+    fn generate_hex(&mut self, len: usize) -> String {
+        let mut buf = vec![0; len];
+        self.fill(buf.as_mut_slice());
+        hex::encode(buf)
+    }
+    /// Generate `len` random bytes and return them as a URL-safe base64 string without padding.
+    fn generate_base64_url(&mut self, len: usize) -> String {
+        let mut buf = vec![0; len];
+        self.fill(buf.as_mut_slice());
+        BASE64_URL_SAFE_NO_PAD.encode(buf)
+    }
+    /// Generate a random (version 4) UUID as defined by RFC 4122.
+    ///
+    /// All but six bits are random: the version nibble is set to `4`, and the two most significant
+    /// bits of the variant are set to `10`.
+    fn generate_uuid_v4(&mut self) -> [u8; 16] {
+        let mut uuid = [0; 16];
+        self.fill(&mut uuid);
+
+        uuid[6] = (uuid[6] & 0x0f) | 0x40;
+        uuid[8] = (uuid[8] & 0x3f) | 0x80;
+
+        uuid
+    }
+    /// Generate a random UUID as in [RandGenerator::generate_uuid_v4], formatted as a lowercase,
+    /// hyphenated string such as `3f0e7d9a-5c1b-4e2f-9a8d-6b7c5d4e3f2a`.
+    fn generate_uuid_v4_string(&mut self) -> String {
+        let uuid = hex::encode(self.generate_uuid_v4());
+
+        format!(
+            "{}-{}-{}-{}-{}",
+            &uuid[..8],
+            &uuid[8..12],
+            &uuid[12..16],
+            &uuid[16..20],
+            &uuid[20..]
+        )
+    }
+    /// Shuffle a slice in place using the Fisher-Yates algorithm, so that every permutation is
+    /// equally likely.
+    fn shuffle<T>(&mut self, slice: &mut [T])
+    where
+        Self: Sized,
+    {
+        for i in (1..slice.len()).rev() {
+            slice.swap(i, uniform_index(self, i + 1));
+        }
+    }
+    /// Choose an element of a slice uniformly at random, or [None] if the slice is empty.
+    fn choose<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T>
+    where
+        Self: Sized,
+    {
+        if slice.is_empty() {
+            return None;
+        }
+
+        slice.get(uniform_index(self, slice.len()))
+    }
+}
+
+impl<G: RandGenerator + ?Sized> RandGenerator for Box<G> {
+    fn fill(&mut self, bytes: &mut [u8]) {
+        (**self).fill(bytes);
+    }
+
+    fn is_cryptographically_secure(&self) -> bool {
+        (**self).is_cryptographically_secure()
+    }
+}
+
+/// Draw an index uniformly from `0..bound` using a random [u64].
+///
+/// Simply taking the remainder would favor low indices whenever `bound` doesn't divide 2^64, so
+/// draws from the incomplete range at the top of [u64] are rejected and redrawn.
+fn uniform_index<G: RandGenerator + ?Sized>(rng: &mut G, bound: usize) -> usize {
+    let bound = bound as u64;
+    // 2^64 % bound, the number of values at the top which would bias the result
+    let excess = (u64::MAX % bound + 1) % bound;
+
+    loop {
+        let mut bytes = [0; 8];
+        rng.fill(&mut bytes);
+
+        let value = u64::from_ne_bytes(bytes);
+
+        if value <= u64::MAX - excess {
+            return (value % bound) as usize;
+        }
+    }
+}
+
+/// Generate `bytes` random bytes and return the fraction of bits which are set, or [None] if
+/// `bytes` is zero and there is nothing to measure.
+///
+/// This is the monobit test, the simplest of statistical randomness checks: a good generator
+/// should produce a ratio very close to 0.5, though passing it says little about quality beyond
+/// the absence of gross bias.
+pub fn monobit_ratio(rng: &mut impl RandGenerator, bytes: usize) -> Option<f64> {
+    if bytes == 0 {
+        return None;
+    }
+
+    let mut buf = vec![0; bytes];
+    rng.fill(buf.as_mut_slice());
+
+    let set: u64 = buf.iter().map(|b| u64::from(b.count_ones())).sum();
+
+    Some(set as f64 / (bytes * 8) as f64)
+}
+
+/// A [RandGenerator] which can be named in benchmarks.
+pub trait RandGeneratorBenchmark: RandGenerator {
+    /// The prefix used for benchmark names of this generator.
+    const PREFIX: &'static str;
+
+    /// The prefix used for benchmark names of this particular instance.
+    ///
+    /// Defaults to [RandGeneratorBenchmark::PREFIX], but may be overridden to include parameters
+    /// chosen at runtime, so that differently-configured instances of the same type are told
+    /// apart.
+    fn prefix(&self) -> String {
+        Self::PREFIX.to_string()
+    }
+}
+
+/// Shared [RandGenerator] core over any [Read] source.
+///
+/// Reads are retried until the buffer is completely full, as a single [Read::read] call is allowed
+/// to return fewer bytes than requested.
+pub struct ReadRng<R: Read> {
+    reader: R,
 }
 
-pub struct FileRng {
-    file: File,
+impl<R: Read> ReadRng<R> {
+    /// Constructs a new instance reading from the given source.
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
 }
 
-impl RandGenerator for FileRng {
+impl<R: Read> RandGenerator for ReadRng<R> {
     fn fill(&mut self, bytes: &mut [u8]) {
-        assert_eq!(
-            bytes.len(),
-            self.file.read(bytes).expect("unable to read from rng file")
-        );
+        let mut filled = 0;
+
+        // loop until the buffer is full or die trying
+        while filled < bytes.len() {
+            match self.reader.read(&mut bytes[filled..]) {
+                Ok(0) => panic!("rng source reached end of file"),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => panic!("unable to read from rng source: {e}"),
+            }
+        }
     }
 }
 
-pub struct DevRandomDirectRng(FileRng);
+/// Adapts a [RandGenerator] into a [Read] source, the inverse of [ReadRng].
+///
+/// Reads always fill the entire buffer, so the reader never reaches end of file; use [Read::take]
+/// to bound it.
+pub struct RandReader<R: RandGenerator> {
+    rng: R,
+}
 
-impl DevRandomDirectRng {
-    pub const PREFIX: &'static str = "rng::sys::random::direct";
+impl<R: RandGenerator> RandReader<R> {
+    /// Constructs a new instance reading from the given generator.
+    pub fn new(rng: R) -> Self {
+        Self { rng }
+    }
+
+    /// Consume the reader, returning the underlying generator.
+    pub fn into_inner(self) -> R {
+        self.rng
+    }
+}
+
+impl<R: RandGenerator> Read for RandReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.rng.fill(buf);
+        Ok(buf.len())
+    }
+}
+
+/// Adapts any [rand] generator, such as [StdRng](rand::rngs::StdRng), into a [RandGenerator], e.g.
+/// to run it through the benchmarks.
+///
+/// Whether the generator is cryptographically secure can't be told from [RngCore] alone, so
+/// [RngCoreGenerator::new] reports it as insecure; use [RngCoreGenerator::from_crypto_rng] for
+/// [CryptoRng] implementors.
+pub struct RngCoreGenerator<R: RngCore> {
+    rng: R,
+    secure: bool,
+}
+
+impl<R: RngCore> RngCoreGenerator<R> {
+    /// Constructs a new instance over the given generator, which is reported as insecure.
+    pub fn new(rng: R) -> Self {
+        Self { rng, secure: false }
+    }
+
+    /// Consume the adapter, returning the underlying generator.
+    pub fn into_inner(self) -> R {
+        self.rng
+    }
+}
+
+impl<R: RngCore + CryptoRng> RngCoreGenerator<R> {
+    /// Constructs a new instance over the given cryptographically secure generator.
+    pub fn from_crypto_rng(rng: R) -> Self {
+        Self { rng, secure: true }
+    }
+}
+
+impl<R: RngCore> RandGenerator for RngCoreGenerator<R> {
+    fn fill(&mut self, bytes: &mut [u8]) {
+        self.rng.fill_bytes(bytes);
+    }
+
+    fn is_cryptographically_secure(&self) -> bool {
+        self.secure
+    }
+}
+
+impl<R: RngCore> RandGeneratorBenchmark for RngCoreGenerator<R> {
+    const PREFIX: &'static str = "rng::rand_core";
+
+    /// Names the benchmark after the wrapped generator's type, without its module path.
+    fn prefix(&self) -> String {
+        let name = std::any::type_name::<R>();
+        let name = name.rsplit("::").next().unwrap_or(name);
+
+        format!("{}::{}", Self::PREFIX, name)
+    }
+}
+
+/// A read result sent back from a [TimeoutReadRng] worker thread, along with the reader itself.
+type TimedRead<R> = (R, io::Result<Vec<u8>>);
+
+/// Reads random data from a source which may block indefinitely, such as `/dev/random` when the
+/// kernel entropy pool is low, giving up after a timeout rather than hanging.
+///
+/// Each read happens on a separate thread. If a read times out, that thread keeps the reader until
+/// the read eventually completes, and fills fail with [ErrorKind::WouldBlock] until then.
+pub struct TimeoutReadRng<R: Read + Send + 'static> {
+    reader: Option<R>,
+    /// The outcome of a read which timed out, delivered once it completes.
+    pending: Option<Receiver<TimedRead<R>>>,
+    timeout: Duration,
+}
+
+impl<R: Read + Send + 'static> TimeoutReadRng<R> {
+    /// Constructs a new instance reading from the given source, failing any fill which takes
+    /// longer than `timeout`.
+    pub fn new(reader: R, timeout: Duration) -> Self {
+        Self {
+            reader: Some(reader),
+            pending: None,
+            timeout,
+        }
+    }
+
+    /// Fill a mutable buffer of bytes with random data, failing with [ErrorKind::TimedOut] if the
+    /// source doesn't produce enough bytes in time.
+    pub fn try_fill(&mut self, bytes: &mut [u8]) -> io::Result<()> {
+        let mut reader = match self.reader.take() {
+            Some(reader) => reader,
+            None => self.recover()?,
+        };
+
+        let (tx, rx) = mpsc::channel();
+        let len = bytes.len();
+
+        thread::spawn(move || {
+            let mut buf = vec![0; len];
+            let result = reader.read_exact(&mut buf).map(|_| buf);
+            // the receiver is gone if the generator was dropped while waiting, which is fine
+            let _ = tx.send((reader, result));
+        });
+
+        match rx.recv_timeout(self.timeout) {
+            Ok((reader, result)) => {
+                self.reader = Some(reader);
+                bytes.copy_from_slice(&result?);
+                Ok(())
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                self.pending = Some(rx);
+                Err(io::Error::new(
+                    ErrorKind::TimedOut,
+                    format!("rng source produced no data within {:?}", self.timeout),
+                ))
+            }
+            Err(RecvTimeoutError::Disconnected) => Err(Self::lost()),
+        }
+    }
+
+    /// Take back the reader from a timed out read if it has since completed, discarding its data.
+    fn recover(&mut self) -> io::Result<R> {
+        let pending = self.pending.take().ok_or_else(Self::lost)?;
+
+        match pending.try_recv() {
+            Ok((reader, _)) => Ok(reader),
+            Err(TryRecvError::Empty) => {
+                self.pending = Some(pending);
+                Err(io::Error::new(
+                    ErrorKind::WouldBlock,
+                    "a previous read from the rng source is still blocked",
+                ))
+            }
+            Err(TryRecvError::Disconnected) => Err(Self::lost()),
+        }
+    }
+
+    fn lost() -> io::Error {
+        io::Error::other("rng source was lost when a read panicked")
+    }
+}
+
+impl<R: Read + Send + 'static> RandGenerator for TimeoutReadRng<R> {
+    fn fill(&mut self, bytes: &mut [u8]) {
+        self.try_fill(bytes)
+            .unwrap_or_else(|e| panic!("unable to read from rng source: {e}"));
+    }
+}
+
+/// Produces bytes sequentially from a fixed slice, the simplest possible reproducible source.
+///
+/// Once the slice is exhausted, output wraps around to its start, so it can never run dry; use a
+/// [ReadRng] over the slice instead to panic when exhausted.
+pub struct FixedRng<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> FixedRng<'a> {
+    /// Constructs a new instance producing bytes from `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty, as there would be nothing to produce.
+    pub fn new(data: &'a [u8]) -> Self {
+        assert!(!data.is_empty(), "fixed rng data must not be empty");
+
+        Self { data, position: 0 }
+    }
+}
+
+impl RandGenerator for FixedRng<'_> {
+    fn is_cryptographically_secure(&self) -> bool {
+        false
+    }
+
+    fn fill(&mut self, bytes: &mut [u8]) {
+        let mut filled = 0;
+
+        while filled < bytes.len() {
+            let n = (bytes.len() - filled).min(self.data.len() - self.position);
+
+            bytes[filled..filled + n].copy_from_slice(&self.data[self.position..self.position + n]);
+
+            filled += n;
+            self.position = (self.position + n) % self.data.len();
+        }
+    }
+}
+
+/// Wraps a [RandGenerator], writing every byte it produces to a sink as well, e.g. to capture the
+/// randomness used by a failing run.
+///
+/// Captured bytes can be replayed by wrapping them in a [ReadRng].
+pub struct TeeRng<R: RandGenerator, W: Write> {
+    rng: R,
+    sink: W,
+}
+
+impl<R: RandGenerator, W: Write> TeeRng<R, W> {
+    /// Constructs a new instance generating from `rng` and writing to `sink`.
+    pub fn new(rng: R, sink: W) -> Self {
+        Self { rng, sink }
+    }
+
+    /// Consume the tee, returning the underlying generator and sink.
+    pub fn into_inner(self) -> (R, W) {
+        (self.rng, self.sink)
+    }
+}
+
+impl<R: RandGenerator, W: Write> RandGenerator for TeeRng<R, W> {
+    fn fill(&mut self, bytes: &mut [u8]) {
+        self.rng.fill(bytes);
+
+        // a capture missing bytes can't be replayed, so don't carry on silently
+        self.sink
+            .write_all(bytes)
+            .unwrap_or_else(|e| panic!("unable to write to tee sink: {e}"));
+    }
+
+    /// Bytes which have been written to the sink are no longer secret, whatever their source.
+    fn is_cryptographically_secure(&self) -> bool {
+        false
+    }
+}
+
+/// Wraps a seedable CSPRNG, reseeding it from the OS whenever the process ID changes, i.e. in a
+/// child process after a `fork()`.
+///
+/// A forked child inherits an exact copy of its parent's generator state, so without reseeding
+/// both processes would go on to produce the same "random" bytes. The process ID is checked before
+/// every fill.
+pub struct ReseedingRng<R: CryptoRng + RngCore + SeedableRng> {
+    rng: R,
+    /// The process ID the generator was last seeded in.
+    pid: u32,
+    pid_source: fn() -> u32,
+    reseeds: usize,
+}
+
+impl<R: CryptoRng + RngCore + SeedableRng> ReseedingRng<R> {
+    /// Constructs a new instance wrapping `rng`, which is assumed to be seeded for this process.
+    pub fn new(rng: R) -> Self {
+        Self::with_pid_source(rng, std::process::id)
+    }
+
+    /// Constructs a new instance reading the process ID from `pid_source`, so that a fork can be
+    /// simulated.
+    fn with_pid_source(rng: R, pid_source: fn() -> u32) -> Self {
+        Self {
+            rng,
+            pid: pid_source(),
+            pid_source,
+            reseeds: 0,
+        }
+    }
+
+    /// How many times the wrapped generator has been reseeded after a process ID change.
+    pub fn reseeds(&self) -> usize {
+        self.reseeds
+    }
+
+    /// Consume the wrapper, returning the underlying generator.
+    pub fn into_inner(self) -> R {
+        self.rng
+    }
+}
+
+impl<R: CryptoRng + RngCore + SeedableRng> RandGenerator for ReseedingRng<R> {
+    fn fill(&mut self, bytes: &mut [u8]) {
+        let pid = (self.pid_source)();
+
+        if pid != self.pid {
+            self.rng = R::from_entropy();
+            self.pid = pid;
+            self.reseeds += 1;
+        }
+
+        self.rng.fill_bytes(bytes);
+    }
+}
+
+/// The number of PBKDF2 iterations used by [SeededChaChaRng::from_password], following OWASP's
+/// recommendation for PBKDF2-HMAC-SHA256.
+pub const PASSWORD_PBKDF2_ITERATIONS: usize = 600_000;
+
+/// A deterministic CSPRNG producing the ChaCha20 keystream for a 32-byte seed.
+///
+/// The same seed always yields the same stream of bytes, across platforms and releases of
+/// [rand_chacha], which makes this useful for reproducing randomized runs. The output is only as
+/// secret as the seed itself.
+pub struct SeededChaChaRng {
+    rng: ChaCha20Rng,
+}
+
+impl SeededChaChaRng {
+    /// Constructs a new instance from the given seed.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self {
+            rng: ChaCha20Rng::from_seed(seed),
+        }
+    }
+
+    /// Constructs a new instance seeded from a password, deriving the seed with
+    /// PBKDF2-HMAC-SHA256 over [PASSWORD_PBKDF2_ITERATIONS] iterations.
+    ///
+    /// The salt should be unique per use, such that the same password doesn't produce the same
+    /// stream everywhere it's used.
+    pub fn from_password(password: &str, salt: &[u8]) -> Result<Self, ErrorStack> {
+        let mut seed = [0; 32];
+
+        openssl::pkcs5::pbkdf2_hmac(
+            password.as_bytes(),
+            salt,
+            PASSWORD_PBKDF2_ITERATIONS,
+            MessageDigest::sha256(),
+            &mut seed,
+        )?;
+
+        Ok(Self::from_seed(seed))
+    }
+}
+
+impl RandGenerator for SeededChaChaRng {
+    fn fill(&mut self, bytes: &mut [u8]) {
+        self.rng.fill_bytes(bytes);
+    }
+
+    /// The stream is reproducible by anyone who knows or can guess the seed or password.
+    fn is_cryptographically_secure(&self) -> bool {
+        false
+    }
+}
+
+/// The default size of the pool in a [PooledRng].
+pub const DEFAULT_POOL_CAPACITY: usize = 4096;
+
+/// Wraps a [RandGenerator], drawing from it in large blocks and serving fills out of a pool, so
+/// that many small fills don't each pay the per-call overhead of the underlying source.
+///
+/// Served bytes are zeroed in the pool, so that they don't linger in memory any longer than they
+/// need to.
+pub struct PooledRng<R: RandGenerator> {
+    rng: R,
+    pool: Vec<u8>,
+    /// The index of the next unused byte in the pool.
+    position: usize,
+}
+
+impl<R: RandGenerator> PooledRng<R> {
+    /// Constructs a new instance with a pool of [DEFAULT_POOL_CAPACITY] bytes.
+    pub fn new(rng: R) -> Self {
+        Self::with_capacity(rng, DEFAULT_POOL_CAPACITY)
+    }
+
+    /// Constructs a new instance with a pool of `capacity` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(rng: R, capacity: usize) -> Self {
+        assert!(capacity > 0, "pool capacity must be non-zero");
+
+        Self {
+            rng,
+            pool: vec![0; capacity],
+            // start out exhausted so that the pool is filled on first use
+            position: capacity,
+        }
+    }
+
+    /// The size of the pool in bytes.
+    pub fn capacity(&self) -> usize {
+        self.pool.len()
+    }
+}
+
+impl<R: RandGenerator> RandGenerator for PooledRng<R> {
+    fn fill(&mut self, bytes: &mut [u8]) {
+        let mut filled = 0;
+
+        while filled < bytes.len() {
+            if self.position == self.pool.len() {
+                self.rng.fill(&mut self.pool);
+                self.position = 0;
+            }
 
+            let n = (bytes.len() - filled).min(self.pool.len() - self.position);
+            let served = &mut self.pool[self.position..self.position + n];
+
+            bytes[filled..filled + n].copy_from_slice(served);
+            served.fill(0);
+
+            filled += n;
+            self.position += n;
+        }
+    }
+
+    fn is_cryptographically_secure(&self) -> bool {
+        self.rng.is_cryptographically_secure()
+    }
+}
+
+impl<R: RandGeneratorBenchmark> RandGeneratorBenchmark for PooledRng<R> {
+    const PREFIX: &'static str = "rng::pooled";
+
+    /// Names the benchmark after the wrapped generator and the pool capacity.
+    fn prefix(&self) -> String {
+        let inner = self.rng.prefix();
+        let inner = inner.strip_prefix("rng::").unwrap_or(&inner);
+
+        format!("{}::{}::{}", Self::PREFIX, inner, self.capacity())
+    }
+}
+
+/// Selects a [RandGenerator] backend at runtime, e.g. from configuration or the command line.
+///
+/// Only non-blocking sources are offered, as `/dev/random` may block indefinitely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RandSource {
+    /// Buffered reads from `/dev/urandom`, see [DevUrandomBufRng].
+    DevUrandomBuffered,
+    /// Unbuffered reads from `/dev/urandom`, see [DevUrandomDirectRng].
+    DevUrandomDirect,
+    /// OpenSSL's CSPRNG, see [OpenSslRng].
+    OpenSsl,
+    /// The `rand` crate's OS generator, see [RandOsRng].
+    RandOs,
+    /// The `rand` crate's thread-local generator, see [RandThreadRng].
+    RandThread,
+}
+
+impl RandSource {
+    /// Every available source.
+    pub const ALL: [RandSource; 5] = [
+        RandSource::DevUrandomBuffered,
+        RandSource::DevUrandomDirect,
+        RandSource::OpenSsl,
+        RandSource::RandOs,
+        RandSource::RandThread,
+    ];
+
+    /// Open the generator for this source.
+    ///
+    /// Unlike the generator constructors, failing to open a device file is returned as an error
+    /// rather than a panic.
+    pub fn open(self) -> io::Result<Box<dyn RandGenerator>> {
+        let urandom = || File::options().read(true).open("/dev/urandom");
+
+        Ok(match self {
+            RandSource::DevUrandomBuffered => {
+                Box::new(DevUrandomBufRng(ReadRng::new(BufReader::new(urandom()?))))
+            }
+            RandSource::DevUrandomDirect => Box::new(DevUrandomDirectRng(ReadRng::new(urandom()?))),
+            RandSource::OpenSsl => Box::new(OpenSslRng::new()),
+            RandSource::RandOs => Box::new(RandOsRng::new()),
+            RandSource::RandThread => Box::new(RandThreadRng::new()),
+        })
+    }
+}
+
+pub struct DevRandomDirectRng(ReadRng<File>);
+
+impl DevRandomDirectRng {
     pub fn new() -> Self {
-        Self(FileRng {
-            file: File::options()
+        Self(ReadRng::new(
+            File::options()
                 .read(true)
                 .open("/dev/random")
                 .expect("unable to open /dev/random direct reader"),
-        })
+        ))
+    }
+
+    /// Open `/dev/random` for direct reads which fail rather than block for longer than
+    /// `timeout`, see [TimeoutReadRng::try_fill].
+    pub fn with_timeout(timeout: Duration) -> TimeoutReadRng<File> {
+        TimeoutReadRng::new(
+            File::options()
+                .read(true)
+                .open("/dev/random")
+                .expect("unable to open /dev/random direct reader"),
+            timeout,
+        )
+    }
+}
+
+impl Default for DevRandomDirectRng {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -70,18 +752,26 @@ impl RandGenerator for DevRandomDirectRng {
     }
 }
 
-pub struct DevUrandomDirectRng(FileRng);
+impl RandGeneratorBenchmark for DevRandomDirectRng {
+    const PREFIX: &'static str = "rng::sys::random::direct";
+}
 
-impl DevUrandomDirectRng {
-    pub const PREFIX: &'static str = "rng::sys::urandom::direct";
+pub struct DevUrandomDirectRng(ReadRng<File>);
 
+impl DevUrandomDirectRng {
     pub fn new() -> Self {
-        Self(FileRng {
-            file: File::options()
+        Self(ReadRng::new(
+            File::options()
                 .read(true)
                 .open("/dev/urandom")
                 .expect("unable to open /dev/urandom direct reader"),
-        })
+        ))
+    }
+}
+
+impl Default for DevUrandomDirectRng {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -91,35 +781,26 @@ impl RandGenerator for DevUrandomDirectRng {
     }
 }
 
-pub struct BufFileRng {
-    file: BufReader<File>,
-}
-
-impl RandGenerator for BufFileRng {
-    fn fill(&mut self, bytes: &mut [u8]) {
-        assert_eq!(
-            bytes.len(),
-            self.file
-                .read(bytes)
-                .expect("unable to read from buffered rng file")
-        );
-    }
+impl RandGeneratorBenchmark for DevUrandomDirectRng {
+    const PREFIX: &'static str = "rng::sys::urandom::direct";
 }
 
-pub struct DevRandomBufRng(BufFileRng);
+pub struct DevRandomBufRng(ReadRng<BufReader<File>>);
 
 impl DevRandomBufRng {
-    pub const PREFIX: &'static str = "rng::sys::random::buffered";
-
     pub fn new() -> Self {
-        Self(BufFileRng {
-            file: BufReader::new(
-                File::options()
-                    .read(true)
-                    .open("/dev/random")
-                    .expect("unable to open /dev/random buffered reader"),
-            ),
-        })
+        Self(ReadRng::new(BufReader::new(
+            File::options()
+                .read(true)
+                .open("/dev/random")
+                .expect("unable to open /dev/random buffered reader"),
+        )))
+    }
+}
+
+impl Default for DevRandomBufRng {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -129,20 +810,26 @@ impl RandGenerator for DevRandomBufRng {
     }
 }
 
-pub struct DevUrandomBufRng(BufFileRng);
+impl RandGeneratorBenchmark for DevRandomBufRng {
+    const PREFIX: &'static str = "rng::sys::random::buffered";
+}
 
-impl DevUrandomBufRng {
-    pub const PREFIX: &'static str = "rng::sys::urandom::buffered";
+pub struct DevUrandomBufRng(ReadRng<BufReader<File>>);
 
+impl DevUrandomBufRng {
     pub fn new() -> Self {
-        Self(BufFileRng {
-            file: BufReader::new(
-                File::options()
-                    .read(true)
-                    .open("/dev/urandom")
-                    .expect("unable to open /dev/urandom buffered reader"),
-            ),
-        })
+        Self(ReadRng::new(BufReader::new(
+            File::options()
+                .read(true)
+                .open("/dev/urandom")
+                .expect("unable to open /dev/urandom buffered reader"),
+        )))
+    }
+}
+
+impl Default for DevUrandomBufRng {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -152,11 +839,14 @@ impl RandGenerator for DevUrandomBufRng {
     }
 }
 
+impl RandGeneratorBenchmark for DevUrandomBufRng {
+    const PREFIX: &'static str = "rng::sys::urandom::buffered";
+}
+
+#[derive(Default)]
 pub struct OpenSslRng {}
 
 impl OpenSslRng {
-    pub const PREFIX: &'static str = "rng::openssl";
-
     pub fn new() -> Self {
         Self {}
     }
@@ -168,17 +858,18 @@ impl RandGenerator for OpenSslRng {
     }
 }
 
+impl RandGeneratorBenchmark for OpenSslRng {
+    const PREFIX: &'static str = "rng::openssl";
+}
+
+#[derive(Default)]
 pub struct RandOsRng {
     source: OsRng,
 }
 
 impl RandOsRng {
-    pub const PREFIX: &'static str = "rng::rand_crate::os";
-
     pub fn new() -> Self {
-        Self {
-            source: OsRng::default(),
-        }
+        Self { source: OsRng }
     }
 }
 
@@ -188,13 +879,16 @@ impl RandGenerator for RandOsRng {
     }
 }
 
+impl RandGeneratorBenchmark for RandOsRng {
+    const PREFIX: &'static str = "rng::rand_crate::os";
+}
+
+#[derive(Default)]
 pub struct RandThreadRng {
     source: ThreadRng,
 }
 
 impl RandThreadRng {
-    pub const PREFIX: &'static str = "rng::rand_crate::thread";
-
     pub fn new() -> Self {
         Self {
             source: ThreadRng::default(),
@@ -207,3 +901,7 @@ impl RandGenerator for RandThreadRng {
         self.source.fill(bytes);
     }
 }
+
+impl RandGeneratorBenchmark for RandThreadRng {
+    const PREFIX: &'static str = "rng::rand_crate::thread";
+}
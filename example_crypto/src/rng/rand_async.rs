@@ -0,0 +1,113 @@
+use crate::rng::rand_sys::{
+    SysRandomBufferedGenerator, SysRandomDirectGenerator, SysUrandomBufferedGenerator,
+    SysUrandomDirectGenerator,
+};
+use crate::rng::{
+    DevRandomBufRng, DevRandomDirectRng, DevUrandomBufRng, DevUrandomDirectRng, RandGenerator,
+    ReadRng,
+};
+use std::fs::File;
+use std::future::Future;
+use std::io::BufReader;
+
+/// A [RandGenerator] which can be filled from async code without blocking the runtime.
+///
+/// Reads are offloaded to [tokio::task::spawn_blocking], so that a read from `/dev/random` waiting
+/// on entropy only ties up a blocking thread rather than an executor thread.
+pub trait AsyncRandGenerator: RandGenerator {
+    /// Fill a mutable buffer of bytes with random data on a blocking thread.
+    fn fill_async<'a>(&'a mut self, bytes: &'a mut [u8]) -> impl Future<Output = ()> + Send + 'a;
+}
+
+/// A [ReadRng] over a file, whose handle can be duplicated and moved to a blocking thread.
+trait FileRng {
+    fn file(&self) -> &File;
+}
+
+impl FileRng for ReadRng<File> {
+    fn file(&self) -> &File {
+        &self.reader
+    }
+}
+
+impl FileRng for ReadRng<BufReader<File>> {
+    fn file(&self) -> &File {
+        self.reader.get_ref()
+    }
+}
+
+/// Fill the buffer by reading from a duplicate of the file's handle on a blocking thread.
+///
+/// The duplicate reads straight from the file, so any data already buffered by a [BufReader] is
+/// left for subsequent synchronous fills.
+fn fill_blocking<'a>(
+    rng: &impl FileRng,
+    bytes: &'a mut [u8],
+) -> impl Future<Output = ()> + Send + 'a {
+    let file = rng
+        .file()
+        .try_clone()
+        .expect("unable to duplicate rng source");
+
+    async move {
+        let len = bytes.len();
+
+        let buf = tokio::task::spawn_blocking(move || {
+            let mut buf = vec![0; len];
+            ReadRng::new(file).fill(&mut buf);
+            buf
+        })
+        .await
+        .expect("rng fill task failed");
+
+        bytes.copy_from_slice(&buf);
+    }
+}
+
+impl AsyncRandGenerator for DevRandomDirectRng {
+    fn fill_async<'a>(&'a mut self, bytes: &'a mut [u8]) -> impl Future<Output = ()> + Send + 'a {
+        fill_blocking(&self.0, bytes)
+    }
+}
+
+impl AsyncRandGenerator for DevRandomBufRng {
+    fn fill_async<'a>(&'a mut self, bytes: &'a mut [u8]) -> impl Future<Output = ()> + Send + 'a {
+        fill_blocking(&self.0, bytes)
+    }
+}
+
+impl AsyncRandGenerator for DevUrandomDirectRng {
+    fn fill_async<'a>(&'a mut self, bytes: &'a mut [u8]) -> impl Future<Output = ()> + Send + 'a {
+        fill_blocking(&self.0, bytes)
+    }
+}
+
+impl AsyncRandGenerator for DevUrandomBufRng {
+    fn fill_async<'a>(&'a mut self, bytes: &'a mut [u8]) -> impl Future<Output = ()> + Send + 'a {
+        fill_blocking(&self.0, bytes)
+    }
+}
+
+impl AsyncRandGenerator for SysRandomDirectGenerator {
+    fn fill_async<'a>(&'a mut self, bytes: &'a mut [u8]) -> impl Future<Output = ()> + Send + 'a {
+        fill_blocking(&self.0, bytes)
+    }
+}
+
+impl AsyncRandGenerator for SysRandomBufferedGenerator {
+    fn fill_async<'a>(&'a mut self, bytes: &'a mut [u8]) -> impl Future<Output = ()> + Send + 'a {
+        fill_blocking(&self.0, bytes)
+    }
+}
+
+impl AsyncRandGenerator for SysUrandomDirectGenerator {
+    fn fill_async<'a>(&'a mut self, bytes: &'a mut [u8]) -> impl Future<Output = ()> + Send + 'a {
+        fill_blocking(&self.0, bytes)
+    }
+}
+
+impl AsyncRandGenerator for SysUrandomBufferedGenerator {
+    fn fill_async<'a>(&'a mut self, bytes: &'a mut [u8]) -> impl Future<Output = ()> + Send + 'a {
+        fill_blocking(&self.0, bytes)
+    }
+}
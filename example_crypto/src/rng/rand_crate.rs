@@ -1,13 +1,14 @@
-use crate::rng::RandGenerator;
+use crate::rng::{RandGenerator, RandGeneratorBenchmark};
 use rand::rngs::{OsRng, ThreadRng};
-use rand::{thread_rng, CryptoRng, Rng, RngCore};
+use rand::{thread_rng, CryptoRng, Rng};
 
 /// CSPRNG built on the [rand] crate's [OsRng] implementation.
+#[derive(Default)]
 pub struct SecureOsGenerator(OsRng);
 
 impl SecureOsGenerator {
     pub fn new() -> Self {
-        Self(OsRng::default())
+        Self(OsRng)
     }
 }
 
@@ -17,6 +18,10 @@ impl RandGenerator for SecureOsGenerator {
     }
 }
 
+impl RandGeneratorBenchmark for SecureOsGenerator {
+    const PREFIX: &'static str = "rng::rand_crate::secure_os";
+}
+
 /// CSPRNG built on the [rand] crate's [ThreadRng] implementation.
 pub struct SecureThreadGenerator(ThreadRng);
 
@@ -26,20 +31,30 @@ impl SecureThreadGenerator {
     }
 }
 
+impl Default for SecureThreadGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RandGenerator for SecureThreadGenerator {
     fn fill(&mut self, bytes: &mut [u8]) {
         self.0.fill(bytes);
     }
 }
 
+impl RandGeneratorBenchmark for SecureThreadGenerator {
+    const PREFIX: &'static str = "rng::rand_crate::secure_thread";
+}
+
 /// Constant assertion that [OsRng] is a [CryptoRng] implementor.
 #[allow(unused)]
-fn assert_secure_os_rng() -> impl Rng + RngCore + CryptoRng {
-    OsRng::default()
+fn assert_secure_os_rng() -> impl Rng + CryptoRng {
+    OsRng
 }
 
 /// Constant assertion that [ThreadRng] is a [CryptoRng] implementor.
 #[allow(unused)]
-fn assert_secure_thread_rng() -> impl Rng + RngCore + CryptoRng {
+fn assert_secure_thread_rng() -> impl Rng + CryptoRng {
     thread_rng()
 }
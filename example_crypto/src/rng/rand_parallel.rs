@@ -0,0 +1,51 @@
+use crate::rng::rand_crate::SecureOsGenerator;
+use crate::rng::{OpenSslRng, RandGenerator, RandOsRng};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use rayon::prelude::*;
+
+/// The size of each chunk of the buffer handed to a worker thread.
+///
+/// Small enough that a multi-megabyte buffer is spread across every thread in the pool, but large
+/// enough that the per-chunk scheduling overhead doesn't dominate the actual generation.
+pub const PARALLEL_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A [RandGenerator] whose underlying source is thread-safe, so that large buffers can be filled
+/// concurrently.
+///
+/// The buffer is split into chunks of [PARALLEL_CHUNK_SIZE] bytes which are filled on the
+/// [rayon] global thread pool. Buffers of a single chunk or less are simply filled in place.
+pub trait ParallelRandGenerator: RandGenerator {
+    /// Fill a mutable buffer of bytes with random data, splitting the work across threads.
+    fn fill_parallel(&mut self, bytes: &mut [u8]);
+}
+
+/// Fill each chunk of the buffer concurrently using the given function.
+fn fill_chunks(bytes: &mut [u8], fill: impl Fn(&mut [u8]) + Sync) {
+    if bytes.len() <= PARALLEL_CHUNK_SIZE {
+        fill(bytes);
+        return;
+    }
+
+    bytes.par_chunks_mut(PARALLEL_CHUNK_SIZE).for_each(&fill);
+}
+
+impl ParallelRandGenerator for OpenSslRng {
+    fn fill_parallel(&mut self, bytes: &mut [u8]) {
+        fill_chunks(bytes, |chunk| {
+            openssl::rand::rand_bytes(chunk).expect("unable to read from OpenSSL rng")
+        });
+    }
+}
+
+impl ParallelRandGenerator for RandOsRng {
+    fn fill_parallel(&mut self, bytes: &mut [u8]) {
+        fill_chunks(bytes, |chunk| OsRng.fill_bytes(chunk));
+    }
+}
+
+impl ParallelRandGenerator for SecureOsGenerator {
+    fn fill_parallel(&mut self, bytes: &mut [u8]) {
+        fill_chunks(bytes, |chunk| OsRng.fill_bytes(chunk));
+    }
+}
@@ -1,96 +1,100 @@
-use crate::rng::RandGenerator;
+use crate::rng::{RandGenerator, RandGeneratorBenchmark, ReadRng};
 use anyhow::Error;
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::BufReader;
 
 /// CSPRNG using `/dev/random` without a buffer.
-pub struct SysRandomDirectGenerator(File);
+pub struct SysRandomDirectGenerator(pub(super) ReadRng<File>);
 
 impl SysRandomDirectGenerator {
     /// Constructs a new instance.
     ///
     /// Returns an error if it cannot open `/dev/random`.
     pub fn new() -> Result<Self, Error> {
-        Ok(Self(File::options().read(true).open("/dev/random")?))
+        Ok(Self(ReadRng::new(
+            File::options().read(true).open("/dev/random")?,
+        )))
     }
 }
 
 impl RandGenerator for SysRandomDirectGenerator {
     fn fill(&mut self, bytes: &mut [u8]) {
-        // try to read the exact amount or die trying
-        assert_eq!(
-            self.0.read(bytes).expect("unable to read random data"),
-            bytes.len()
-        );
+        self.0.fill(bytes);
     }
 }
 
+impl RandGeneratorBenchmark for SysRandomDirectGenerator {
+    const PREFIX: &'static str = "rng::rand_sys::random::direct";
+}
+
 /// CSPRNG using `/dev/random` with a buffer.
-pub struct SysRandomBufferedGenerator(BufReader<File>);
+pub struct SysRandomBufferedGenerator(pub(super) ReadRng<BufReader<File>>);
 
 impl SysRandomBufferedGenerator {
     /// Constructs a new instance.
     ///
     /// Returns an error if it cannot open `/dev/random`.
     pub fn new() -> Result<Self, Error> {
-        Ok(Self(BufReader::new(
+        Ok(Self(ReadRng::new(BufReader::new(
             File::options().read(true).open("/dev/random")?,
-        )))
+        ))))
     }
 }
 
 impl RandGenerator for SysRandomBufferedGenerator {
     fn fill(&mut self, bytes: &mut [u8]) {
-        // try to read the exact amount or die trying
-        assert_eq!(
-            self.0.read(bytes).expect("unable to read random data"),
-            bytes.len()
-        );
+        self.0.fill(bytes);
     }
 }
 
+impl RandGeneratorBenchmark for SysRandomBufferedGenerator {
+    const PREFIX: &'static str = "rng::rand_sys::random::buffered";
+}
+
 /// CSPRNG using `/dev/urandom` without a buffer.
-pub struct SysUrandomDirectGenerator(File);
+pub struct SysUrandomDirectGenerator(pub(super) ReadRng<File>);
 
 impl SysUrandomDirectGenerator {
     /// Constructs a new instance.
     ///
-    /// Returns an error if it cannot open '/dev/urandom`.
+    /// Returns an error if it cannot open `/dev/urandom`.
     pub fn new() -> Result<Self, Error> {
-        Ok(Self(File::options().read(true).open("/dev/urandom")?))
+        Ok(Self(ReadRng::new(
+            File::options().read(true).open("/dev/urandom")?,
+        )))
     }
 }
 
 impl RandGenerator for SysUrandomDirectGenerator {
     fn fill(&mut self, bytes: &mut [u8]) {
-        // try to read the exact amount or die trying
-        assert_eq!(
-            self.0.read(bytes).expect("unable to read random data"),
-            bytes.len()
-        );
+        self.0.fill(bytes);
     }
 }
 
+impl RandGeneratorBenchmark for SysUrandomDirectGenerator {
+    const PREFIX: &'static str = "rng::rand_sys::urandom::direct";
+}
+
 /// CSPRNG using `/dev/urandom` with a buffer.
-pub struct SysUrandomBufferedGenerator(BufReader<File>);
+pub struct SysUrandomBufferedGenerator(pub(super) ReadRng<BufReader<File>>);
 
 impl SysUrandomBufferedGenerator {
     /// Constructs a new instance.
     ///
     /// Returns an error if it cannot open `/dev/urandom`.
     pub fn new() -> Result<Self, Error> {
-        Ok(Self(BufReader::new(
+        Ok(Self(ReadRng::new(BufReader::new(
             File::options().read(true).open("/dev/urandom")?,
-        )))
+        ))))
     }
 }
 
 impl RandGenerator for SysUrandomBufferedGenerator {
     fn fill(&mut self, bytes: &mut [u8]) {
-        // try to read the exact amount or die trying
-        assert_eq!(
-            self.0.read(bytes).expect("unable to read random data"),
-            bytes.len()
-        );
+        self.0.fill(bytes);
     }
 }
+
+impl RandGeneratorBenchmark for SysUrandomBufferedGenerator {
+    const PREFIX: &'static str = "rng::rand_sys::urandom::buffered";
+}
@@ -1,7 +1,18 @@
 use super::{
-    DevUrandomBufRng, DevUrandomDirectRng, OpenSslRng, RandGenerator, RandOsRng, RandThreadRng,
+    monobit_ratio, DevUrandomBufRng, DevUrandomDirectRng, FixedRng, OpenSslRng, PooledRng,
+    RandGenerator, RandGeneratorBenchmark, RandOsRng, RandReader, RandSource, RandThreadRng,
+    ReadRng, ReseedingRng, RngCoreGenerator, SeededChaChaRng, SysUrandomBuffered, SysUrandomDirect,
+    TeeRng, TimeoutReadRng,
 };
 
+use base64::prelude::BASE64_URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
 #[test]
 fn test_urandom_buf_rng() {
     let mut rng = DevUrandomBufRng::new();
@@ -41,3 +52,452 @@ fn test_rand_thread_rng() {
     assert_ne!([0; 32], rng.generate_array::<32>());
     assert_ne!(vec![0; 32], rng.generate_vec::<32>());
 }
+
+#[test]
+fn test_generate_hex() {
+    let mut rng = OpenSslRng::new();
+
+    for len in [0, 1, 16, 32, 33] {
+        let s = rng.generate_hex(len);
+
+        assert_eq!(2 * len, s.len());
+        assert!(s
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)));
+    }
+}
+
+#[test]
+fn test_generate_base64_url() {
+    let mut rng = OpenSslRng::new();
+
+    for len in [0, 1, 16, 32, 33] {
+        let s = rng.generate_base64_url(len);
+        let decoded = BASE64_URL_SAFE_NO_PAD
+            .decode(s.as_bytes())
+            .expect("unable to decode base64");
+
+        assert_eq!(len, decoded.len());
+    }
+}
+
+/// Reader which hands out at most `chunk` bytes per call, counting up from zero.
+struct ChunkedReader {
+    chunk: usize,
+    next: u8,
+}
+
+impl Read for ChunkedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.chunk);
+
+        for b in buf[..len].iter_mut() {
+            *b = self.next;
+            self.next = self.next.wrapping_add(1);
+        }
+
+        Ok(len)
+    }
+}
+
+#[test]
+fn test_read_rng_short_reads() {
+    let mut rng = ReadRng::new(ChunkedReader { chunk: 7, next: 0 });
+
+    let mut buf = [0; 4096];
+    rng.fill(&mut buf);
+
+    // every byte was filled despite each read returning at most 7 bytes
+    assert!(buf.iter().enumerate().all(|(i, b)| *b == i as u8));
+}
+
+#[test]
+#[should_panic(expected = "end of file")]
+fn test_read_rng_eof() {
+    let mut rng = ReadRng::new(io::empty());
+    rng.generate_array::<32>();
+}
+
+/// Fill a 4096-byte buffer, returning it.
+fn fill_4096(rng: &mut impl RandGenerator) -> Vec<u8> {
+    let mut buf = vec![0; 4096];
+    rng.fill(buf.as_mut_slice());
+    buf
+}
+
+#[test]
+fn test_sys_and_dev_generators_match() {
+    let outputs = [
+        fill_4096(&mut DevUrandomBufRng::new()),
+        fill_4096(&mut SysUrandomBuffered::new().expect("unable to open /dev/urandom")),
+        fill_4096(&mut DevUrandomDirectRng::new()),
+        fill_4096(&mut SysUrandomDirect::new().expect("unable to open /dev/urandom")),
+    ];
+
+    for output in outputs.iter() {
+        assert_eq!(4096, output.len());
+        // the odds of 64 consecutive zero bytes from a CSPRNG are negligible
+        assert!(output.chunks(64).all(|c| c.iter().any(|b| *b != 0)));
+    }
+}
+
+#[test]
+fn test_rand_reader_copy() {
+    let mut sink = Vec::new();
+
+    let copied = io::copy(
+        &mut RandReader::new(OpenSslRng::new()).take(1024),
+        &mut sink,
+    )
+    .expect("unable to copy random data");
+
+    assert_eq!(1024, copied);
+    assert_eq!(1024, sink.len());
+    assert_ne!(vec![0; 1024], sink);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_urandom_buf_rng_fill_async() {
+    use super::AsyncRandGenerator;
+
+    let mut rng = DevUrandomBufRng::new();
+    let mut buf = [0; 256];
+
+    rng.fill_async(&mut buf).await;
+
+    assert_ne!([0; 256], buf);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_fill_parallel() {
+    use super::{ParallelRandGenerator, RandCrateOs, PARALLEL_CHUNK_SIZE};
+
+    fn check(rng: &mut impl ParallelRandGenerator) {
+        let mut buf = vec![0; 4 * 1024 * 1024];
+
+        rng.fill_parallel(&mut buf);
+
+        assert_eq!(4 * 1024 * 1024, buf.len());
+
+        // every chunk handed to a worker must have been filled; an all-zero 1 KiB window from a
+        // real rng source is vanishingly unlikely
+        for (i, window) in buf.chunks(1024).enumerate() {
+            assert!(
+                window.iter().any(|b| *b != 0),
+                "window {i} was left unfilled"
+            );
+        }
+
+        // buffers smaller than a single chunk are filled in place
+        let mut small = vec![0; PARALLEL_CHUNK_SIZE / 2];
+        rng.fill_parallel(&mut small);
+        assert_ne!(vec![0; PARALLEL_CHUNK_SIZE / 2], small);
+    }
+
+    check(&mut OpenSslRng::new());
+    check(&mut RandOsRng::new());
+    check(&mut RandCrateOs::new());
+}
+
+#[test]
+fn test_seeded_chacha_from_password() {
+    let stream = |password: &str, salt: &[u8]| {
+        SeededChaChaRng::from_password(password, salt)
+            .expect("unable to derive seed")
+            .generate_vec::<64>()
+    };
+
+    let first = stream("correct horse battery staple", b"salt");
+
+    assert_eq!(first, stream("correct horse battery staple", b"salt"));
+    assert_ne!(first, stream("correct horse battery staple", b"pepper"));
+    assert_ne!(first, stream("incorrect horse battery staple", b"salt"));
+}
+
+#[test]
+fn test_generate_uuid_v4() {
+    let mut rng = OpenSslRng::new();
+
+    let first = rng.generate_uuid_v4();
+    let second = rng.generate_uuid_v4();
+
+    assert_ne!(first, second);
+
+    for uuid in [first, second] {
+        assert_eq!(0x40, uuid[6] & 0xf0, "version nibble must be 4");
+        assert_eq!(0x80, uuid[8] & 0xc0, "variant bits must be 10xx");
+    }
+
+    // the fixed bits are set even when the random bytes are all ones or all zeroes
+    assert_eq!(0x4f, FixedRng::new(&[0xff]).generate_uuid_v4()[6]);
+    assert_eq!(0xbf, FixedRng::new(&[0xff]).generate_uuid_v4()[8]);
+    assert_eq!(
+        "00000000-0000-4000-8000-000000000000",
+        FixedRng::new(&[0]).generate_uuid_v4_string()
+    );
+
+    let uuid = rng.generate_uuid_v4_string();
+    let groups: Vec<usize> = uuid.split('-').map(str::len).collect();
+
+    assert_eq!(vec![8, 4, 4, 4, 12], groups);
+    assert_eq!(Some('4'), uuid.chars().nth(14));
+}
+
+#[test]
+fn test_rng_core_generator() {
+    let generate = || RngCoreGenerator::new(StdRng::seed_from_u64(0)).generate_array::<16>();
+
+    let expected = {
+        let mut bytes = [0; 16];
+        StdRng::seed_from_u64(0).fill_bytes(&mut bytes);
+        bytes
+    };
+
+    assert_eq!(expected, generate());
+    assert_eq!(generate(), generate());
+
+    let insecure = RngCoreGenerator::new(StdRng::seed_from_u64(0));
+    let secure = RngCoreGenerator::from_crypto_rng(StdRng::seed_from_u64(0));
+
+    assert!(!insecure.is_cryptographically_secure());
+    assert!(secure.is_cryptographically_secure());
+    assert_eq!("rng::rand_core::StdRng", secure.prefix());
+}
+
+#[test]
+fn test_pooled_rng() {
+    // a pool smaller than the fixed data, so fills span refills
+    let data: Vec<u8> = (1..=32).collect();
+    let mut rng = PooledRng::with_capacity(FixedRng::new(&data), 5);
+
+    let mut first = [0; 3];
+    let mut second = [0; 12];
+
+    rng.fill(&mut first);
+    rng.fill(&mut second);
+
+    assert_eq!([1, 2, 3], first);
+    assert_eq!([4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15], second);
+    assert!(!rng.is_cryptographically_secure());
+    assert!(PooledRng::new(OpenSslRng::new()).is_cryptographically_secure());
+}
+
+#[test]
+fn test_benchmark_prefix() {
+    assert_eq!("rng::openssl", OpenSslRng::new().prefix());
+
+    let small = PooledRng::with_capacity(OpenSslRng::new(), 256);
+    let large = PooledRng::with_capacity(OpenSslRng::new(), 65536);
+
+    assert_eq!("rng::pooled::openssl::256", small.prefix());
+    assert_eq!("rng::pooled::openssl::65536", large.prefix());
+}
+
+#[test]
+fn test_rand_source_open() {
+    for source in RandSource::ALL {
+        let mut rng = source.open().expect("unable to open rand source");
+
+        assert_ne!([0; 32], rng.generate_array::<32>(), "{source:?}");
+    }
+}
+
+#[test]
+fn test_monobit_ratio() {
+    for source in RandSource::ALL {
+        let mut rng = source.open().expect("unable to open rand source");
+        let ratio = monobit_ratio(&mut rng, 64 * 1024).expect("no bits were measured");
+
+        assert!((0.48..=0.52).contains(&ratio), "{source:?}: {ratio}");
+    }
+
+    // an empty sample has no ratio at all, rather than NaN
+    assert_eq!(None, monobit_ratio(&mut OpenSslRng::new(), 0));
+}
+
+#[test]
+fn test_tee_rng() {
+    let mut rng = TeeRng::new(OpenSslRng::new(), Vec::new());
+
+    let generated = rng.generate_array::<32>();
+    let (_, captured) = rng.into_inner();
+
+    assert_eq!(generated.as_slice(), captured.as_slice());
+
+    // the capture replays the same bytes
+    let mut replay = ReadRng::new(captured.as_slice());
+
+    assert_eq!(generated, replay.generate_array::<32>());
+}
+
+#[test]
+fn test_fixed_rng() {
+    let mut rng = FixedRng::new(&[1, 2, 3, 4]);
+
+    assert_eq!([1, 2, 3, 4], rng.generate_array::<4>());
+
+    // once drained, output wraps around to the start, including mid-fill
+    assert_eq!([1, 2, 3], rng.generate_array::<3>());
+    assert_eq!([4, 1, 2, 3, 4, 1, 2, 3, 4, 1], rng.generate_array::<10>());
+}
+
+#[test]
+#[should_panic(expected = "must not be empty")]
+fn test_fixed_rng_empty() {
+    FixedRng::new(&[]);
+}
+
+#[test]
+fn test_generate_many() {
+    let arrays = OpenSslRng::new().generate_many::<16>(100);
+
+    assert_eq!(100, arrays.len());
+    assert!(arrays.iter().any(|a| *a != arrays[0]));
+
+    // arrays are filled in sequence, as if generated one at a time
+    let data: Vec<u8> = (0..=255).collect();
+    let arrays = FixedRng::new(&data).generate_many::<4>(3);
+
+    assert_eq!(vec![[0, 1, 2, 3], [4, 5, 6, 7], [8, 9, 10, 11]], arrays);
+}
+
+/// A reader which blocks forever without producing any bytes.
+struct StalledReader;
+
+impl Read for StalledReader {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            std::thread::park();
+        }
+    }
+}
+
+#[test]
+fn test_timeout_read_rng_times_out() {
+    let mut rng = TimeoutReadRng::new(StalledReader, Duration::from_millis(50));
+    let started = Instant::now();
+
+    let err = rng.try_fill(&mut [0; 32]).unwrap_err();
+
+    assert_eq!(io::ErrorKind::TimedOut, err.kind());
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    // the reader is still stuck in the first read
+    let err = rng.try_fill(&mut [0; 32]).unwrap_err();
+    assert_eq!(io::ErrorKind::WouldBlock, err.kind());
+}
+
+#[test]
+fn test_timeout_read_rng_fills() {
+    let data: Vec<u8> = (0..64).collect();
+    let mut rng = TimeoutReadRng::new(io::Cursor::new(data), Duration::from_secs(5));
+
+    assert_eq!([0, 1, 2, 3], rng.generate_array::<4>());
+    assert_eq!([4, 5, 6, 7], rng.generate_array::<4>());
+
+    // running out of data is an error rather than a timeout
+    let err = rng.try_fill(&mut [0; 64]).unwrap_err();
+    assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+}
+
+#[test]
+fn test_is_cryptographically_secure() {
+    assert!(OpenSslRng::new().is_cryptographically_secure());
+    assert!(!FixedRng::new(&[1, 2, 3]).is_cryptographically_secure());
+    assert!(!SeededChaChaRng::from_seed([7; 32]).is_cryptographically_secure());
+
+    for source in RandSource::ALL {
+        let rng = source.open().expect("unable to open rng source");
+        assert!(rng.is_cryptographically_secure(), "{source:?}");
+    }
+
+    // boxing delegates to the wrapped generator
+    let boxed: Box<dyn RandGenerator> = Box::new(FixedRng::new(&[1]));
+    assert!(!boxed.is_cryptographically_secure());
+
+    let tee = TeeRng::new(OpenSslRng::new(), Vec::new());
+    assert!(!tee.is_cryptographically_secure());
+}
+
+#[test]
+fn test_shuffle() {
+    let mut rng = OpenSslRng::new();
+    let original: Vec<u32> = (0..100).chain(0..10).collect();
+    let mut shuffled = original.clone();
+
+    rng.shuffle(&mut shuffled);
+
+    // the odds of a shuffle leaving 110 elements in place are negligible
+    assert_ne!(original, shuffled);
+
+    let mut sorted = shuffled.clone();
+    sorted.sort();
+
+    let mut expected = original.clone();
+    expected.sort();
+
+    assert_eq!(expected, sorted);
+
+    // degenerate slices are left as they are
+    let mut empty: [u32; 0] = [];
+    rng.shuffle(&mut empty);
+
+    let mut single = [7];
+    rng.shuffle(&mut single);
+    assert_eq!([7], single);
+}
+
+#[test]
+fn test_choose() {
+    let mut rng = OpenSslRng::new();
+
+    assert_eq!(Some(&42), rng.choose(&[42]));
+    assert_eq!(None, rng.choose::<u32>(&[]));
+
+    let values = [1, 2, 3, 4];
+
+    for _ in 0..100 {
+        assert!(values.contains(rng.choose(&values).unwrap()));
+    }
+
+    // an all-zero source always picks the first element
+    let mut fixed = FixedRng::new(&[0]);
+    assert_eq!(Some(&1), fixed.choose(&values));
+}
+
+/// The process ID reported to [test_reseeding_rng], changed to simulate a fork.
+static MOCK_PID: AtomicU32 = AtomicU32::new(100);
+
+fn mock_pid() -> u32 {
+    MOCK_PID.load(Ordering::SeqCst)
+}
+
+#[test]
+fn test_reseeding_rng() {
+    let mut rng = ReseedingRng::with_pid_source(StdRng::from_seed([7; 32]), mock_pid);
+    let mut reference = StdRng::from_seed([7; 32]);
+
+    let mut expected = [0; 32];
+    reference.fill_bytes(&mut expected);
+
+    // without a fork, the wrapped generator is used as-is
+    assert_eq!(expected, rng.generate_array::<32>());
+    assert_eq!(0, rng.reseeds());
+
+    MOCK_PID.store(101, Ordering::SeqCst);
+
+    // the child no longer follows the parent's sequence
+    reference.fill_bytes(&mut expected);
+    assert_ne!(expected, rng.generate_array::<32>());
+    assert_eq!(1, rng.reseeds());
+
+    // reseeding only happens once per process
+    rng.generate_array::<32>();
+    assert_eq!(1, rng.reseeds());
+
+    let mut rng = ReseedingRng::new(StdRng::from_entropy());
+    rng.generate_array::<32>();
+    assert_eq!(0, rng.reseeds());
+}
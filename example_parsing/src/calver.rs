@@ -0,0 +1,127 @@
+//! Parsing calendar versions, such as `2024.01` or `2024.01.15`, into structured
+//! [CalendarVersion] objects with [nom].
+//!
+//! Calendar versions are kept entirely separate from
+//! [SemanticVersion](crate::semver::SemanticVersion), as the two schemes can't be meaningfully
+//! compared with one another.
+//!
+//! ## Displaying and Parsing
+//!
+//! A calendar version is a four-digit year and a month, optionally followed by a day, delimited by
+//! `.` in the form `YYYY.MM[.DD]`. The month and day may be written with or without a leading zero,
+//! and are validated to lie within `1..=12` and `1..=31` respectively. The day is not checked
+//! against the length of the month, so `2024.02.31` is accepted.
+//!
+//! [Display] always zero-pads the month and day:
+//!
+//! ```rust
+//! use example_parsing::calver::CalendarVersion;
+//!
+//! let version = CalendarVersion::parse("2024.1.5").unwrap();
+//!
+//! assert_eq!(CalendarVersion::new(2024, 1, 5), version);
+//! assert_eq!("2024.01.05", version.to_string());
+//! ```
+//!
+//! ## Comparing and Sorting
+//!
+//! Versions are ordered chronologically. A version without a day refers to the month as a whole,
+//! and so sorts before any version with a day in that same month.
+
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Context, Error, Result};
+use nom::bytes::complete::take_while_m_n;
+use nom::character::complete::char;
+use nom::combinator::{map_res, opt, verify};
+use nom::sequence::preceded;
+use nom::IResult;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Representation of a calendar version with an optional day.
+///
+/// Fields are declared from most to least significant, so the derived [Ord] is chronological.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct CalendarVersion {
+    pub year: u16,
+    pub month: u8,
+    pub day: Option<u8>,
+}
+
+impl CalendarVersion {
+    /// Create a new calendar version including a day.
+    pub const fn new(year: u16, month: u8, day: u8) -> Self {
+        Self {
+            year,
+            month,
+            day: Some(day),
+        }
+    }
+
+    /// Create a calendar version with only a year and a month.
+    pub const fn monthly(year: u16, month: u8) -> Self {
+        Self {
+            year,
+            month,
+            day: None,
+        }
+    }
+
+    /// Parse a [CalendarVersion] from a string.
+    pub fn parse<S: AsRef<str>>(s: S) -> Result<Self> {
+        Self::from_str(s.as_ref())
+    }
+
+    /// Parse the input using [nom], returning a [IResult].
+    fn nom_parse(input: &str) -> IResult<&str, Self> {
+        // parse between min and max base-10 digits into an integer
+        fn digits<'a, T: FromStr>(
+            min: usize,
+            max: usize,
+        ) -> impl FnMut(&'a str) -> IResult<&'a str, T> {
+            map_res(
+                take_while_m_n(min, max, |c: char| c.is_ascii_digit()),
+                |s: &'a str| s.parse::<T>(),
+            )
+        }
+
+        // the year is always four digits
+        let (input, year) = digits(4, 4)(input)?;
+        let (input, _) = char('.')(input)?;
+        let (input, month) = verify(digits(1, 2), |m: &u8| (1..=12).contains(m))(input)?;
+        // optionally parse a '.' followed by the day
+        let (input, day) = opt(preceded(
+            char('.'),
+            verify(digits(1, 2), |d: &u8| (1..=31).contains(d)),
+        ))(input)?;
+
+        Ok((input, Self { year, month, day }))
+    }
+}
+
+impl Display for CalendarVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = if let Some(day) = self.day {
+            format!("{:04}.{:02}.{:02}", self.year, self.month, day)
+        } else {
+            format!("{:04}.{:02}", self.year, self.month)
+        };
+
+        f.pad(&s)
+    }
+}
+
+impl FromStr for CalendarVersion {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match CalendarVersion::nom_parse(s).map_err(|e| e.to_owned()) {
+            Ok(("", v)) => Ok(v),
+            Ok((rest, _)) => Err(Error::msg(format!("unexpected trailing input: {rest:?}"))),
+            Err(e) => Err(e.into()),
+        }
+        .context("Unable to parse input as calendar version")
+    }
+}
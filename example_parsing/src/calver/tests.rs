@@ -0,0 +1,78 @@
+use super::CalendarVersion;
+
+#[test]
+fn test_parse_valid() {
+    assert_eq!(
+        CalendarVersion::monthly(2024, 1),
+        CalendarVersion::parse("2024.01").unwrap()
+    );
+    assert_eq!(
+        CalendarVersion::new(2024, 1, 15),
+        CalendarVersion::parse("2024.01.15").unwrap()
+    );
+    assert_eq!(
+        CalendarVersion::new(1999, 12, 31),
+        CalendarVersion::parse("1999.12.31").unwrap()
+    );
+    // leading zeroes are optional
+    assert_eq!(
+        CalendarVersion::new(2024, 3, 7),
+        CalendarVersion::parse("2024.3.7").unwrap()
+    );
+}
+
+#[test]
+fn test_parse_invalid() {
+    for input in [
+        "2024.13",
+        "2024.00",
+        "2024.01.32",
+        "2024.01.00",
+        "24.01",
+        "2024",
+        "2024.",
+        "2024.01.",
+        "2024.001",
+        "2024.01.15.1",
+        "v2024.01",
+    ] {
+        assert!(
+            CalendarVersion::parse(input).is_err(),
+            "{input} should not parse"
+        );
+    }
+}
+
+#[test]
+fn test_display() {
+    assert_eq!("2024.01", CalendarVersion::monthly(2024, 1).to_string());
+    assert_eq!("2024.01.05", CalendarVersion::new(2024, 1, 5).to_string());
+    assert_eq!(
+        "[2024.01   ]",
+        format!("[{:<10}]", CalendarVersion::monthly(2024, 1))
+    );
+
+    for input in ["2024.01", "2024.12.31"] {
+        assert_eq!(input, CalendarVersion::parse(input).unwrap().to_string());
+    }
+}
+
+#[test]
+fn test_ordering() {
+    let v = |s: &str| CalendarVersion::parse(s).unwrap();
+
+    assert!(v("2024.01") < v("2024.02"));
+    assert!(v("2023.12.31") < v("2024.01"));
+    assert!(v("2024.01.31") < v("2024.02.01"));
+    // a month as a whole sorts before any day within it
+    assert!(v("2024.01") < v("2024.01.01"));
+    assert_eq!(v("2024.01"), v("2024.1"));
+
+    let mut versions = vec![v("2024.02"), v("2024.01.15"), v("2023.06"), v("2024.01")];
+    versions.sort();
+
+    assert_eq!(
+        vec![v("2023.06"), v("2024.01"), v("2024.01.15"), v("2024.02")],
+        versions
+    );
+}
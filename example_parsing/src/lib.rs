@@ -1 +1,2 @@
+pub mod calver;
 pub mod semver;
@@ -30,6 +30,47 @@
 //! [SemanticVersion] implements [FromStr], and provides a `parse` function which internally calls
 //! the [FromStr] implementation.
 //!
+//! The underlying [nom] parser is exposed as [parser], so that larger grammars can embed versions:
+//!
+//! ```rust
+//! use example_parsing::semver::{self, SemanticVersion};
+//! use nom::bytes::complete::take_until;
+//! use nom::character::complete::char;
+//! use nom::sequence::separated_pair;
+//!
+//! let (_, (name, version)) =
+//!     separated_pair(take_until("@"), char('@'), semver::parser)("libfoo@1.2.3").unwrap();
+//!
+//! assert_eq!("libfoo", name);
+//! assert_eq!(SemanticVersion::new(1, 2, 3), version);
+//! ```
+//!
+//! ## Compile-Time Versions
+//!
+//! The [nom] parser can't run in `const` contexts, so the [semver!](crate::semver!) macro parses a
+//! string literal at compile time instead, rejecting invalid versions as a compile error:
+//!
+//! ```rust
+//! use example_parsing::semver::SemanticVersion;
+//!
+//! const MINIMUM: SemanticVersion = example_parsing::semver!("v1.2.3");
+//!
+//! assert_eq!(SemanticVersion::new(1, 2, 3), MINIMUM);
+//! ```
+//!
+//! ## Wildcard Patterns
+//!
+//! Constraints such as `1.*` or `1.2.x` are parsed into a separate [VersionPattern] type, keeping
+//! [SemanticVersion] strict about what it accepts. A wildcard may be written as `*`, `x`, or `X` in
+//! the minor or bugfix position, and a wildcard minor version may only be followed by a wildcard
+//! bugfix version. [VersionPattern::matches] tests a [SemanticVersion] against the pattern.
+//!
+//! ## Ranges
+//!
+//! Inclusive ranges such as `1.0.0 - 2.0.0` are parsed into a [VersionRange], using the same
+//! parser for both endpoints. The hyphen must be surrounded by whitespace, and the lower bound may
+//! not sort after the upper bound.
+//!
 //! ## Serde
 //!
 //! [serde] support is also included with [Serialize] and [Deserialize] support. The default
@@ -67,17 +108,42 @@
 #[cfg(test)]
 mod tests;
 
-use anyhow::{Context, Error, Result};
-use nom::character::{complete::char, complete::digit1};
-use nom::combinator::{map, opt};
-use nom::sequence::preceded;
+use anyhow::{anyhow, Context, Error, Result};
+use nom::branch::alt;
+use nom::character::{complete::char, complete::digit1, complete::space1};
+use nom::combinator::{map_res, opt, value};
+use nom::error::{Error as NomError, ErrorKind};
+use nom::sequence::{delimited, preceded};
 use nom::IResult;
 use serde::{de, ser, Deserializer, Serializer};
 use std::cmp::Ordering;
 
 use std::fmt::{Display, Formatter};
+use std::io::Read;
+use std::ops::Add;
 use std::str::FromStr;
 
+/// Construct a [SemanticVersion] from a string literal at compile time, failing to compile if the
+/// literal isn't a valid version.
+///
+/// ```rust
+/// use example_parsing::semver::SemanticVersion;
+///
+/// assert_eq!(SemanticVersion::abridged(0, 2), example_parsing::semver!("0.2"));
+/// ```
+///
+/// ```compile_fail
+/// let _ = example_parsing::semver!("1.2.x");
+/// ```
+#[macro_export]
+macro_rules! semver {
+    ($version:literal) => {{
+        const VERSION: $crate::semver::SemanticVersion =
+            $crate::semver::SemanticVersion::from_literal($version);
+        VERSION
+    }};
+}
+
 /// Representation of a semantic version with an optional bugfix revision.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct SemanticVersion {
@@ -88,7 +154,7 @@ pub struct SemanticVersion {
 
 impl SemanticVersion {
     /// Create a new, full semantic version.
-    pub fn new(major: u64, minor: u64, bugfix: u64) -> Self {
+    pub const fn new(major: u64, minor: u64, bugfix: u64) -> Self {
         Self {
             major,
             minor,
@@ -97,7 +163,7 @@ impl SemanticVersion {
     }
 
     /// Create an abridged (major/minor only) semantic version.
-    pub fn abridged(major: u64, minor: u64) -> Self {
+    pub const fn abridged(major: u64, minor: u64) -> Self {
         Self {
             major,
             minor,
@@ -105,31 +171,194 @@ impl SemanticVersion {
         }
     }
 
+    /// Parse a [SemanticVersion] in a `const` context, accepting the same syntax as
+    /// [SemanticVersion::parse].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input isn't a valid version, which is a compile error when evaluated as a
+    /// constant. Prefer the [semver!](crate::semver!) macro, which guarantees exactly that.
+    pub const fn from_literal(s: &str) -> Self {
+        let bytes = s.as_bytes();
+
+        // skip an optional 'v' prefix
+        let start = if !bytes.is_empty() && bytes[0] == b'v' {
+            1
+        } else {
+            0
+        };
+
+        let (major, i) = Self::const_component(bytes, start);
+        let i = Self::const_dot(bytes, i);
+        let (minor, i) = Self::const_component(bytes, i);
+
+        if i == bytes.len() {
+            return Self::abridged(major, minor);
+        }
+
+        let i = Self::const_dot(bytes, i);
+        let (bugfix, i) = Self::const_component(bytes, i);
+
+        if i != bytes.len() {
+            panic!("unexpected trailing characters in semantic version");
+        }
+
+        Self::new(major, minor, bugfix)
+    }
+
+    /// Parse a run of base-10 digits starting at `i`, returning the value and the index after it.
+    const fn const_component(bytes: &[u8], mut i: usize) -> (u64, usize) {
+        let start = i;
+        let mut value: u64 = 0;
+
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            value = match value.checked_mul(10) {
+                Some(v) => match v.checked_add((bytes[i] - b'0') as u64) {
+                    Some(v) => v,
+                    None => panic!("semantic version component overflows u64"),
+                },
+                None => panic!("semantic version component overflows u64"),
+            };
+
+            i += 1;
+        }
+
+        if i == start {
+            panic!("expected digits in semantic version");
+        }
+
+        (value, i)
+    }
+
+    /// Expect a '.' at `i`, returning the index after it.
+    const fn const_dot(bytes: &[u8], i: usize) -> usize {
+        if i >= bytes.len() || bytes[i] != b'.' {
+            panic!("expected '.' in semantic version");
+        }
+
+        i + 1
+    }
+
     /// Format this [SemanticVersion] to a string with a `v` prefix.
     pub fn prefixed(&self) -> String {
         format!("v{}", self.to_string())
     }
 
+    /// Format this [SemanticVersion] to a string with a `v` prefix, an alias of
+    /// [SemanticVersion::prefixed].
+    pub fn as_prefixed(&self) -> String {
+        self.prefixed()
+    }
+
     /// Parse a [SemanticVersion] from a string.
     pub fn parse<S: AsRef<str>>(s: S) -> Result<Self> {
         Self::from_str(s.as_ref())
     }
 
+    /// Parse a [SemanticVersion] from UTF-8 bytes, ignoring a trailing newline, such as the
+    /// contents of a `VERSION` file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let s = std::str::from_utf8(bytes).context("Semantic version is not valid UTF-8")?;
+
+        Self::from_str(s.trim_end_matches(['\r', '\n']))
+    }
+
+    /// Read a [SemanticVersion] from the given reader until end of file, as in
+    /// [SemanticVersion::from_bytes].
+    pub fn from_reader<R: Read>(mut r: R) -> Result<Self> {
+        let mut bytes = Vec::new();
+        r.read_to_end(&mut bytes)
+            .context("Unable to read semantic version")?;
+
+        Self::from_bytes(&bytes)
+    }
+
+    /// Whether this version can safely be used in place of `other`, i.e. whether upgrading from
+    /// `other` to this version is non-breaking.
+    ///
+    /// This is the case when both share the same major version and this version is at least
+    /// `other`. Following caret requirements, a major version of zero makes no compatibility
+    /// promises, so `0.x` versions must also share the same minor version.
+    pub fn is_compatible_with(&self, other: &SemanticVersion) -> bool {
+        if self.major != other.major {
+            return false;
+        }
+
+        if self.major == 0 && self.minor != other.minor {
+            return false;
+        }
+
+        self >= other
+    }
+
+    /// Whether this version and `other` are equal, treating a missing bugfix version on either side
+    /// as a wildcard, e.g. whether a version is in the `1.2` line.
+    ///
+    /// Unlike [Eq], `1.2` loosely equals `1.2.5`. Two full versions are only loosely equal if they
+    /// are strictly equal.
+    pub fn loosely_eq(&self, other: &Self) -> bool {
+        self.major == other.major
+            && self.minor == other.minor
+            && (self.bugfix.is_none() || other.bugfix.is_none() || self.bugfix == other.bugfix)
+    }
+
+    /// The next major version, resetting the minor and bugfix versions to zero.
+    ///
+    /// Returns an error rather than wrapping if the major version is already [u64::MAX].
+    pub fn bump_major(&self) -> Result<Self> {
+        Ok(Self::new(Self::checked_bump(self.major, "major")?, 0, 0))
+    }
+
+    /// The next minor version, resetting the bugfix version to zero.
+    ///
+    /// Returns an error rather than wrapping if the minor version is already [u64::MAX].
+    pub fn bump_minor(&self) -> Result<Self> {
+        Ok(Self::new(
+            self.major,
+            Self::checked_bump(self.minor, "minor")?,
+            0,
+        ))
+    }
+
+    /// The next bugfix version. An abridged version is treated as having a bugfix version of zero,
+    /// so bumping `1.2` yields `1.2.1`.
+    ///
+    /// Returns an error rather than wrapping if the bugfix version is already [u64::MAX].
+    pub fn bump_bugfix(&self) -> Result<Self> {
+        Ok(Self::new(
+            self.major,
+            self.minor,
+            Self::checked_bump(self.bugfix.unwrap_or(0), "bugfix")?,
+        ))
+    }
+
+    fn checked_bump(component: u64, name: &str) -> Result<u64> {
+        component
+            .checked_add(1)
+            .ok_or_else(|| anyhow!("Unable to bump {name} version {component}: overflow"))
+    }
+
+    /// Parse the entire input, returning [None] on failure without allocating an error.
+    fn parse_exact(input: &str) -> Option<Self> {
+        match Self::nom_parse(input) {
+            Ok(("", version)) => Some(version),
+            _ => None,
+        }
+    }
+
     /// Parse the input using [nom], returning a [IResult].
     fn nom_parse(input: &str) -> IResult<&str, Self> {
         // parse an optional 'v' prefix
         let (input, _) = opt(char('v'))(input)?;
-        // as long as characters are base-10 digits, collect them and parse into a u64
-        let (input, major) = map(digit1, |s: &str| s.parse::<u64>().unwrap())(input)?;
+        // as long as characters are base-10 digits, collect them and parse into a u64; a number
+        // too large for a u64 is a parse error
+        let (input, major) = map_res(digit1, str::parse::<u64>)(input)?;
         // parse a '.'
         let (input, _) = char('.')(input)?;
         // as long as characters are base-10 digits, collect them and parse into a u64
-        let (input, minor) = map(digit1, |s: &str| s.parse::<u64>().unwrap())(input)?;
+        let (input, minor) = map_res(digit1, str::parse::<u64>)(input)?;
         // optionally parse a '.' followed by base-10 digits, collect those into an Option<u64>
-        let (input, bugfix) = opt(preceded(
-            char('.'),
-            map(digit1, |s: &str| s.parse::<u64>().unwrap()),
-        ))(input)?;
+        let (input, bugfix) = opt(preceded(char('.'), map_res(digit1, str::parse::<u64>)))(input)?;
 
         Ok((
             input,
@@ -142,13 +371,36 @@ impl SemanticVersion {
     }
 }
 
+/// The default version is the full version `0.0.0`, rather than the abridged `0.0`, so that it
+/// sorts before any other version.
+impl Default for SemanticVersion {
+    fn default() -> Self {
+        Self::new(0, 0, 0)
+    }
+}
+
 impl Display for SemanticVersion {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if let Some(bugfix) = self.bugfix.as_ref() {
-            write!(f, "{}.{}.{}", self.major, self.minor, *bugfix)
+        // format into a string first so that width, fill, and alignment apply to the whole version
+        let s = if let Some(bugfix) = self.bugfix.as_ref() {
+            format!("{}.{}.{}", self.major, self.minor, *bugfix)
         } else {
-            write!(f, "{}.{}", self.major, self.minor)
-        }
+            format!("{}.{}", self.major, self.minor)
+        };
+
+        f.pad(&s)
+    }
+}
+
+impl From<SemanticVersion> for String {
+    fn from(version: SemanticVersion) -> Self {
+        version.to_string()
+    }
+}
+
+impl From<&SemanticVersion> for String {
+    fn from(version: &SemanticVersion) -> Self {
+        version.to_string()
     }
 }
 
@@ -208,6 +460,266 @@ impl PartialOrd for SemanticVersion {
     }
 }
 
+/// Compares against a version string, which is never equal if it isn't a valid version.
+///
+/// The string is parsed without allocating, and must contain nothing but the version.
+impl PartialEq<str> for SemanticVersion {
+    fn eq(&self, other: &str) -> bool {
+        SemanticVersion::parse_exact(other).is_some_and(|other| *self == other)
+    }
+}
+
+impl PartialEq<&str> for SemanticVersion {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+/// Orders against a version string, which is incomparable if it isn't a valid version.
+impl PartialOrd<str> for SemanticVersion {
+    fn partial_cmp(&self, other: &str) -> Option<Ordering> {
+        SemanticVersion::parse_exact(other).map(|other| self.cmp(&other))
+    }
+}
+
+impl PartialOrd<&str> for SemanticVersion {
+    fn partial_cmp(&self, other: &&str) -> Option<Ordering> {
+        self.partial_cmp(*other)
+    }
+}
+
+/// An amount to advance a [SemanticVersion] by, component by component, such as "two minor
+/// versions" for `VersionDelta { minor: 2, ..Default::default() }`.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct VersionDelta {
+    pub major: u64,
+    pub minor: u64,
+    pub bugfix: u64,
+}
+
+/// Add each component of the delta to the version. Unlike the `bump_*` methods, lower components
+/// are not reset, so `1.2.3 + 0.1.0` is `1.3.3`.
+///
+/// An abridged version is treated as having a bugfix version of zero, so the result is always a
+/// full version. Following the `bump_*` methods, overflowing any component is an error rather than
+/// wrapping or saturating, which is why the output is a [Result].
+impl Add<VersionDelta> for SemanticVersion {
+    type Output = Result<SemanticVersion>;
+
+    fn add(self, delta: VersionDelta) -> Self::Output {
+        let checked = |component: u64, amount: u64, name: &str| {
+            component.checked_add(amount).ok_or_else(|| {
+                anyhow!("Unable to add {amount} to {name} version {component}: overflow")
+            })
+        };
+
+        Ok(Self::new(
+            checked(self.major, delta.major, "major")?,
+            checked(self.minor, delta.minor, "minor")?,
+            checked(self.bugfix.unwrap_or(0), delta.bugfix, "bugfix")?,
+        ))
+    }
+}
+
+/// Parse a [SemanticVersion] from the start of the input as a [nom] combinator, returning the
+/// remaining input.
+///
+/// Unlike [SemanticVersion::parse], trailing input is not an error, so that versions can be
+/// embedded in larger grammars.
+pub fn parser(input: &str) -> IResult<&str, SemanticVersion> {
+    SemanticVersion::nom_parse(input)
+}
+
+/// Sort versions in ascending order and remove exact duplicates.
+///
+/// Duplicates are determined by [Eq], so an abridged version and the full version with the same
+/// major and minor, such as `1.0` and `1.0.0`, are both kept, with the abridged one sorting last.
+pub fn sorted_unique(mut versions: Vec<SemanticVersion>) -> Vec<SemanticVersion> {
+    versions.sort();
+    versions.dedup();
+    versions
+}
+
+/// Sort versions in ascending order, guaranteeing that versions which compare as equal keep the
+/// order they were given in.
+///
+/// See [sort_versions_stable_by_key] for sorting items which carry a version alongside other data,
+/// where stability actually makes a difference.
+pub fn sort_versions_stable(versions: &mut [SemanticVersion]) {
+    sort_versions_stable_by_key(versions, |version| version);
+}
+
+/// Sort items by the version extracted by `key` in ascending order, keeping items with equal
+/// versions in the order they were given in, e.g. so that metadata attached to each version stays
+/// in insertion order.
+pub fn sort_versions_stable_by_key<T, F>(items: &mut [T], key: F)
+where
+    F: Fn(&T) -> &SemanticVersion,
+{
+    // slice::sort_by is a stable sort, unlike slice::sort_unstable_by
+    items.sort_by(|a, b| key(a).cmp(key(b)));
+}
+
+/// The greatest of the given versions, or [None] if there are none.
+///
+/// This follows the ordering of [SemanticVersion], so an abridged version such as `1.0` is chosen
+/// over the full version `1.0.0`.
+pub fn max_version<I>(versions: I) -> Option<SemanticVersion>
+where
+    I: IntoIterator<Item = SemanticVersion>,
+{
+    versions.into_iter().max()
+}
+
+/// The least of the given versions, or [None] if there are none.
+///
+/// This follows the ordering of [SemanticVersion], so a full version such as `1.0.0` is chosen
+/// over the abridged version `1.0`.
+pub fn min_version<I>(versions: I) -> Option<SemanticVersion>
+where
+    I: IntoIterator<Item = SemanticVersion>,
+{
+    versions.into_iter().min()
+}
+
+/// A version pattern which may contain wildcards in place of the minor or bugfix versions, such
+/// as `1.*` or `1.2.x`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VersionPattern {
+    pub major: u64,
+    /// The minor version to match, or [None] to match any minor version.
+    pub minor: Option<u64>,
+    /// The bugfix version to match, or [None] to match any bugfix version.
+    pub bugfix: Option<u64>,
+}
+
+impl VersionPattern {
+    /// Parse a [VersionPattern] from a string.
+    pub fn parse<S: AsRef<str>>(s: S) -> Result<Self> {
+        Self::from_str(s.as_ref())
+    }
+
+    /// Whether the given version matches this pattern.
+    ///
+    /// An abridged version has no bugfix version, and so only matches patterns with a wildcard or
+    /// omitted bugfix version.
+    pub fn matches(&self, version: &SemanticVersion) -> bool {
+        self.major == version.major
+            && self.minor.is_none_or(|minor| minor == version.minor)
+            && self
+                .bugfix
+                .is_none_or(|bugfix| Some(bugfix) == version.bugfix)
+    }
+
+    /// Parse the input using [nom], returning a [IResult].
+    fn nom_parse(input: &str) -> IResult<&str, Self> {
+        // a wildcard is parsed as None, a number as Some
+        fn component(input: &str) -> IResult<&str, Option<u64>> {
+            alt((
+                value(None, alt((char('*'), char('x'), char('X')))),
+                map_res(digit1, |s: &str| s.parse::<u64>().map(Some)),
+            ))(input)
+        }
+
+        // parse an optional 'v' prefix
+        let (input, _) = opt(char('v'))(input)?;
+        // the major version may not be a wildcard
+        let (input, major) = map_res(digit1, str::parse::<u64>)(input)?;
+        let (input, _) = char('.')(input)?;
+        let (input, minor) = component(input)?;
+        // the bugfix version is optional, and may itself be a wildcard
+        let (rest, bugfix) = opt(preceded(char('.'), component))(input)?;
+
+        // a wildcard minor version can't be followed by a specific bugfix version, e.g. `1.*.3`
+        if minor.is_none() && matches!(bugfix, Some(Some(_))) {
+            return Err(nom::Err::Failure(NomError::new(input, ErrorKind::Verify)));
+        }
+
+        Ok((
+            rest,
+            Self {
+                major,
+                minor,
+                bugfix: bugfix.flatten(),
+            },
+        ))
+    }
+}
+
+impl Display for VersionPattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (self.minor, self.bugfix) {
+            (None, _) => write!(f, "{}.*", self.major),
+            (Some(minor), None) => write!(f, "{}.{}.*", self.major, minor),
+            (Some(minor), Some(bugfix)) => write!(f, "{}.{}.{}", self.major, minor, bugfix),
+        }
+    }
+}
+
+impl FromStr for VersionPattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        VersionPattern::nom_parse(s)
+            .map(|(_, v)| v)
+            .map_err(|e| e.to_owned())
+            .context("Unable to parse input as version pattern")
+    }
+}
+
+/// An inclusive range of versions, written as `lower - upper`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VersionRange {
+    pub lower: SemanticVersion,
+    pub upper: SemanticVersion,
+}
+
+impl VersionRange {
+    /// Parse a [VersionRange] from a string.
+    pub fn parse<S: AsRef<str>>(s: S) -> Result<Self> {
+        Self::from_str(s.as_ref())
+    }
+
+    /// Whether the given version falls within this range, including either bound.
+    pub fn contains(&self, version: &SemanticVersion) -> bool {
+        &self.lower <= version && version <= &self.upper
+    }
+
+    /// Parse the input using [nom], returning a [IResult].
+    fn nom_parse(input: &str) -> IResult<&str, Self> {
+        let (input, lower) = SemanticVersion::nom_parse(input)?;
+        // require whitespace around the hyphen
+        let (input, _) = delimited(space1, char('-'), space1)(input)?;
+        let (rest, upper) = SemanticVersion::nom_parse(input)?;
+
+        // a reversed range could never contain anything
+        if lower > upper {
+            return Err(nom::Err::Failure(NomError::new(input, ErrorKind::Verify)));
+        }
+
+        Ok((rest, Self { lower, upper }))
+    }
+}
+
+impl Display for VersionRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} - {}", self.lower, self.upper)
+    }
+}
+
+impl FromStr for VersionRange {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match VersionRange::nom_parse(s).map_err(|e| e.to_owned()) {
+            Ok(("", v)) => Ok(v),
+            Ok((rest, _)) => Err(Error::msg(format!("unexpected trailing input: {rest:?}"))),
+            Err(e) => Err(e.into()),
+        }
+        .context("Unable to parse input as version range")
+    }
+}
+
 impl ser::Serialize for SemanticVersion {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
@@ -226,11 +738,18 @@ impl<'de> de::Visitor<'de> for SemanticVersionVisitor {
         formatter.write_str("a semantic version including at least major and minor versions, optionally a bugfix version, delimited by '.', and optionally prefixed with a literal 'v'")
     }
 
+    /// Fails with a concise message naming the offending value, rather than the full context chain
+    /// of the [FromStr] error, as the message is likely to end up in front of whoever wrote the
+    /// config.
     fn visit_str<E>(self, v: &str) -> std::result::Result<Self::Value, E>
     where
         E: de::Error,
     {
-        v.parse().map_err(E::custom)
+        v.parse().map_err(|_| {
+            E::custom(format!(
+                "invalid semantic version {v:?}: expected MAJOR.MINOR[.PATCH]"
+            ))
+        })
     }
 }
 
@@ -1,8 +1,12 @@
-use super::SemanticVersion;
+use super::{
+    max_version, min_version, parser, sort_versions_stable, sort_versions_stable_by_key,
+    sorted_unique, SemanticVersion, VersionDelta, VersionPattern, VersionRange,
+};
 
 use serde::{Deserialize, Serialize};
 use serde_json;
 
+use std::io::Cursor;
 use std::str::FromStr;
 
 #[test]
@@ -13,6 +17,107 @@ fn test_parse_strip_leading_zeroes() {
         SemanticVersion::parse("0009.008.07").expect("unable to parse with leading zeroes")
     );
 }
+
+#[test]
+fn test_default() {
+    assert_eq!(SemanticVersion::new(0, 0, 0), SemanticVersion::default());
+    assert_eq!(
+        r#""0.0.0""#,
+        serde_json::to_string(&SemanticVersion::default()).expect("unable to serialize default")
+    );
+}
+
+#[test]
+fn test_loosely_eq() {
+    let line = SemanticVersion::abridged(1, 2);
+
+    assert!(line.loosely_eq(&SemanticVersion::new(1, 2, 5)));
+    assert!(SemanticVersion::new(1, 2, 5).loosely_eq(&line));
+    assert!(line.loosely_eq(&SemanticVersion::abridged(1, 2)));
+    assert!(!line.loosely_eq(&SemanticVersion::new(1, 3, 0)));
+    assert!(!line.loosely_eq(&SemanticVersion::new(2, 2, 0)));
+    assert!(!SemanticVersion::new(1, 2, 4).loosely_eq(&SemanticVersion::new(1, 2, 5)));
+
+    // strict equality is unaffected
+    assert_ne!(line, SemanticVersion::new(1, 2, 5));
+}
+
+#[test]
+fn test_is_compatible_with() {
+    let v = |s: &str| SemanticVersion::parse(s).unwrap();
+
+    assert!(v("1.4.0").is_compatible_with(&v("1.2.0")));
+    assert!(v("1.2.0").is_compatible_with(&v("1.2.0")));
+    assert!(!v("1.2.0").is_compatible_with(&v("1.4.0")));
+    assert!(!v("2.0.0").is_compatible_with(&v("1.9.9")));
+    assert!(!v("0.3.1").is_compatible_with(&v("0.2.0")));
+    assert!(v("0.3.1").is_compatible_with(&v("0.3.0")));
+}
+
+#[test]
+fn test_version_pattern() {
+    let v = |s: &str| SemanticVersion::parse(s).unwrap();
+
+    let minor = VersionPattern::parse("1.2.x").unwrap();
+    assert!(minor.matches(&v("1.2.9")));
+    assert!(!minor.matches(&v("1.3.0")));
+
+    let major = VersionPattern::parse("1.*").unwrap();
+    assert!(major.matches(&v("1.99.0")));
+    assert!(major.matches(&v("1.0")));
+    assert!(!major.matches(&v("2.0.0")));
+
+    let exact = VersionPattern::parse("v1.2.3").unwrap();
+    assert!(exact.matches(&v("1.2.3")));
+    assert!(!exact.matches(&v("1.2")));
+
+    assert_eq!(major, VersionPattern::parse("1.X.*").unwrap());
+    assert_eq!("1.*", major.to_string());
+    assert_eq!("1.2.*", minor.to_string());
+    assert!(VersionPattern::parse("1.*.3").is_err());
+    assert!(VersionPattern::parse("*.1").is_err());
+    // components too large for a u64 are rejected rather than panicking
+    assert!(VersionPattern::parse("1.99999999999999999999").is_err());
+    assert!(VersionPattern::parse("99999999999999999999.*").is_err());
+}
+
+#[test]
+fn test_sorted_unique() {
+    let v = |s: &str| SemanticVersion::parse(s).unwrap();
+
+    let versions = [
+        "1.2.3", "1.0", "0.9.1", "2.0", "1.0.0", "1.2.3", "1.0", "0.9.1", "1.10.0",
+    ]
+    .map(v)
+    .to_vec();
+
+    assert_eq!(
+        ["0.9.1", "1.0.0", "1.0", "1.2.3", "1.10.0", "2.0"]
+            .map(v)
+            .to_vec(),
+        sorted_unique(versions)
+    );
+}
+
+#[test]
+fn test_from_reader() {
+    assert_eq!(
+        SemanticVersion::new(1, 2, 3),
+        SemanticVersion::from_reader(Cursor::new(b"v1.2.3\n")).expect("unable to read version")
+    );
+    assert_eq!(
+        SemanticVersion::abridged(0, 4),
+        SemanticVersion::from_bytes(b"0.4\r\n").expect("unable to parse version")
+    );
+}
+
+#[test]
+fn test_from_bytes_invalid_utf8() {
+    let err = SemanticVersion::from_bytes(b"v1.\xff.3").expect_err("invalid UTF-8 was parsed");
+
+    assert_eq!("Semantic version is not valid UTF-8", err.to_string());
+}
+
 #[test]
 fn test_display() {
     // abridged, no prefix
@@ -197,6 +302,27 @@ fn test_deserialize() {
     );
 }
 
+#[test]
+fn test_deserialize_error_message() {
+    let e = serde_json::from_str::<Container>(r#"{"version":"abc"}"#)
+        .expect_err("deserialized an invalid version");
+
+    // serde_json appends the position of the error
+    assert_eq!(
+        r#"invalid semantic version "abc": expected MAJOR.MINOR[.PATCH] at line 1 column 16"#,
+        e.to_string()
+    );
+
+    // a component too large for a u64 is reported the same way rather than panicking
+    let e = serde_json::from_str::<Container>(r#"{"version":"99999999999999999999.0"}"#)
+        .expect_err("deserialized an out-of-range version");
+
+    assert_eq!(
+        r#"invalid semantic version "99999999999999999999.0": expected MAJOR.MINOR[.PATCH] at line 1 column 35"#,
+        e.to_string()
+    );
+}
+
 #[test]
 fn test_serialize() {
     // full
@@ -261,3 +387,305 @@ fn test_clap() {
 
     assert_eq!(SemanticVersion::new(0, 1, 2), args.version);
 }
+
+#[test]
+fn test_bump() {
+    let v = SemanticVersion::new(1, 2, 3);
+
+    assert_eq!(SemanticVersion::new(2, 0, 0), v.bump_major().unwrap());
+    assert_eq!(SemanticVersion::new(1, 3, 0), v.bump_minor().unwrap());
+    assert_eq!(SemanticVersion::new(1, 2, 4), v.bump_bugfix().unwrap());
+    assert_eq!(
+        SemanticVersion::new(1, 2, 1),
+        SemanticVersion::abridged(1, 2).bump_bugfix().unwrap()
+    );
+}
+
+#[test]
+fn test_bump_overflow() {
+    let max = SemanticVersion::new(u64::MAX, u64::MAX, u64::MAX);
+
+    assert!(max.bump_major().is_err());
+    assert!(max.bump_minor().is_err());
+    assert!(max.bump_bugfix().is_err());
+
+    // only the bumped component needs headroom, lower components are reset
+    assert_eq!(
+        SemanticVersion::new(1, 0, 0),
+        SemanticVersion::new(0, u64::MAX, u64::MAX)
+            .bump_major()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_display_padding() {
+    assert_eq!("   1.2.3", format!("{:>8}", SemanticVersion::new(1, 2, 3)));
+    assert_eq!("1.2.3   ", format!("{:<8}", SemanticVersion::new(1, 2, 3)));
+    assert_eq!(
+        "--1.2---",
+        format!("{:-^8}", SemanticVersion::abridged(1, 2))
+    );
+    // width is a minimum, so longer versions are never truncated
+    assert_eq!(
+        "10.20.30",
+        format!("{:>4}", SemanticVersion::new(10, 20, 30))
+    );
+}
+
+#[test]
+fn test_semver_macro() {
+    const PINNED: SemanticVersion = crate::semver!("1.2.3");
+
+    assert_eq!(SemanticVersion::new(1, 2, 3), PINNED);
+    assert_eq!(SemanticVersion::new(1, 2, 3), crate::semver!("v1.2.3"));
+    assert_eq!(SemanticVersion::abridged(10, 0), crate::semver!("10.0"));
+    assert_eq!(
+        SemanticVersion::new(u64::MAX, 0, 7),
+        crate::semver!("18446744073709551615.000.07")
+    );
+}
+
+#[test]
+fn test_from_literal_matches_parse() {
+    for s in ["0.2", "v1.2", "1.23.4", "v0.5.6", "0009.008.07"] {
+        assert_eq!(
+            SemanticVersion::parse(s).unwrap(),
+            SemanticVersion::from_literal(s),
+            "{s}"
+        );
+    }
+}
+
+#[test]
+fn test_from_literal_invalid() {
+    for s in [
+        "",
+        "v",
+        "1",
+        "1.",
+        "1.2.",
+        ".1.2",
+        "1.2.3.4",
+        "1.2.x",
+        "18446744073709551616.0",
+    ] {
+        assert!(
+            std::panic::catch_unwind(|| SemanticVersion::from_literal(s)).is_err(),
+            "{s}"
+        );
+    }
+}
+
+#[test]
+fn test_version_range() {
+    let range = VersionRange::parse("1.0.0 - 2.0.0").expect("unable to parse range");
+
+    assert_eq!(SemanticVersion::new(1, 0, 0), range.lower);
+    assert_eq!(SemanticVersion::new(2, 0, 0), range.upper);
+    assert_eq!("1.0.0 - 2.0.0", range.to_string());
+
+    assert!(range.contains(&SemanticVersion::new(1, 5, 0)));
+    // both bounds are inclusive
+    assert!(range.contains(&SemanticVersion::new(1, 0, 0)));
+    assert!(range.contains(&SemanticVersion::new(2, 0, 0)));
+    assert!(!range.contains(&SemanticVersion::new(2, 0, 1)));
+    assert!(!range.contains(&SemanticVersion::new(0, 9, 9)));
+
+    assert_eq!(range, VersionRange::parse("v1.0.0   -   v2.0.0").unwrap());
+}
+
+#[test]
+fn test_version_range_invalid() {
+    // reversed
+    assert!(VersionRange::parse("2.0.0 - 1.0.0").is_err());
+    // missing whitespace around the hyphen
+    assert!(VersionRange::parse("1.0.0-2.0.0").is_err());
+    // missing upper bound
+    assert!(VersionRange::parse("1.0.0 - ").is_err());
+    // trailing input after the upper bound
+    assert!(VersionRange::parse("1.0.0 - 2.0.0 - 3.0.0").is_err());
+    assert!(VersionRange::parse("1.0.0 - 2.0.0garbage").is_err());
+}
+
+#[test]
+fn test_compare_str() {
+    let v = SemanticVersion::new(1, 2, 3);
+
+    assert!(v == "1.2.3");
+    assert!(v == "v1.2.3");
+    assert!(v == *"1.2.3");
+    assert!(v != "1.2");
+    assert!(v != "1.2.4");
+    // invalid versions are never equal, rather than panicking
+    assert!(v != "");
+    assert!(v != "not a version");
+    assert!(v != "1.2.3 trailing");
+    assert!(v != "99999999999999999999.0");
+    assert_eq!(None, v.partial_cmp(&"1.99999999999999999999"));
+
+    assert!(v < "1.3.0");
+    assert!(v > "1.2.2");
+    assert!(v <= "1.2.3");
+    // an abridged version sorts after any full version with the same major/minor
+    assert!(v < "1.2");
+    assert_eq!(None, v.partial_cmp(&"1.x"));
+}
+
+#[test]
+fn test_add_delta() {
+    let minor = VersionDelta {
+        major: 0,
+        minor: 1,
+        bugfix: 0,
+    };
+
+    // lower components are carried over rather than reset
+    assert_eq!(
+        SemanticVersion::new(1, 3, 3),
+        (SemanticVersion::new(1, 2, 3) + minor).unwrap()
+    );
+    assert_eq!(
+        SemanticVersion::new(3, 2, 5),
+        (SemanticVersion::new(1, 2, 3)
+            + VersionDelta {
+                major: 2,
+                minor: 0,
+                bugfix: 2,
+            })
+        .unwrap()
+    );
+
+    // abridged versions are promoted to full versions
+    assert_eq!(
+        SemanticVersion::new(1, 3, 0),
+        (SemanticVersion::abridged(1, 2) + minor).unwrap()
+    );
+    assert_eq!(
+        SemanticVersion::new(1, 2, 0),
+        (SemanticVersion::abridged(1, 2) + VersionDelta::default()).unwrap()
+    );
+}
+
+#[test]
+fn test_add_delta_overflow() {
+    let version = SemanticVersion::new(1, u64::MAX, 3);
+
+    assert!((version.clone() + VersionDelta::default()).is_ok());
+    assert!((version
+        + VersionDelta {
+            major: 0,
+            minor: 1,
+            bugfix: 0,
+        })
+    .is_err());
+}
+
+#[test]
+fn test_parser_combinator() {
+    use nom::bytes::complete::take_while1;
+    use nom::character::complete::char;
+    use nom::combinator::all_consuming;
+    use nom::sequence::separated_pair;
+    use nom::IResult;
+
+    fn package(input: &str) -> IResult<&str, (&str, SemanticVersion)> {
+        all_consuming(separated_pair(
+            take_while1(|c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+            char('@'),
+            parser,
+        ))(input)
+    }
+
+    assert_eq!(
+        Ok(("", ("libfoo", SemanticVersion::new(1, 2, 3)))),
+        package("libfoo@1.2.3")
+    );
+    assert_eq!(
+        Ok(("", ("libfoo", SemanticVersion::abridged(0, 2)))),
+        package("libfoo@v0.2")
+    );
+
+    // the parser itself leaves trailing input for the enclosing grammar
+    assert_eq!(
+        Ok((" rest", SemanticVersion::new(1, 2, 3))),
+        parser("1.2.3 rest")
+    );
+
+    assert!(package("libfoo@1.2.x").is_err());
+    assert!(package("@1.2.3").is_err());
+}
+
+#[test]
+fn test_into_string() {
+    let version = SemanticVersion::new(1, 2, 3);
+
+    assert_eq!("1.2.3", String::from(&version));
+    assert_eq!("v1.2.3", version.as_prefixed());
+    assert_eq!(version.prefixed(), version.as_prefixed());
+
+    let owned: String = version.into();
+    assert_eq!("1.2.3", owned);
+
+    let abridged: String = SemanticVersion::abridged(0, 2).into();
+    assert_eq!("0.2", abridged);
+}
+
+#[test]
+fn test_max_min_version() {
+    let versions = vec![
+        SemanticVersion::new(0, 9, 12),
+        SemanticVersion::new(1, 0, 0),
+        SemanticVersion::abridged(1, 0),
+        SemanticVersion::new(0, 1, 0),
+    ];
+
+    assert_eq!(
+        Some(SemanticVersion::abridged(1, 0)),
+        max_version(versions.clone())
+    );
+    assert_eq!(Some(SemanticVersion::new(0, 1, 0)), min_version(versions));
+
+    // the abridged version outranks its full counterpart in either order
+    assert_eq!(
+        Some(SemanticVersion::abridged(1, 0)),
+        max_version([
+            SemanticVersion::abridged(1, 0),
+            SemanticVersion::new(1, 0, 0)
+        ])
+    );
+    assert_eq!(
+        Some(SemanticVersion::new(1, 0, 0)),
+        min_version([
+            SemanticVersion::abridged(1, 0),
+            SemanticVersion::new(1, 0, 0)
+        ])
+    );
+
+    assert_eq!(None, max_version(Vec::new()));
+    assert_eq!(None, min_version(Vec::new()));
+}
+
+#[test]
+fn test_sort_versions_stable() {
+    let v = |s: &str| SemanticVersion::parse(s).unwrap();
+
+    let mut versions = vec![v("1.2.0"), v("1.0"), v("0.9.1"), v("1.0.0")];
+    sort_versions_stable(&mut versions);
+
+    assert_eq!(vec![v("0.9.1"), v("1.0.0"), v("1.0"), v("1.2.0")], versions);
+
+    let mut pairs: Vec<(SemanticVersion, usize)> =
+        ["2.0.0", "1.0", "2.0.0", "1.0.0", "1.0", "2.0.0"]
+            .into_iter()
+            .enumerate()
+            .map(|(index, s)| (v(s), index))
+            .collect();
+
+    sort_versions_stable_by_key(&mut pairs, |(version, _)| version);
+
+    let indices: Vec<usize> = pairs.iter().map(|(_, index)| *index).collect();
+
+    // equal versions keep their input order
+    assert_eq!(vec![3, 1, 4, 0, 2, 5], indices);
+}
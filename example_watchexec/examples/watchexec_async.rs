@@ -1,36 +1,77 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::ops::AddAssign;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use tokio::sync::Mutex;
 use tracing::Level;
 use watchexec::action::ActionHandler;
+use watchexec::command::{Command, Program};
+use watchexec::job::{CommandState, Job};
 use watchexec::Watchexec;
-use watchexec_events::filekind::FileEventKind;
-use watchexec_events::Tag;
+use watchexec_events::{Event, Priority, ProcessEnd, Source, Tag};
 use watchexec_signals::Signal;
 
-use examples_common::logging::LogLevelFilter;
+use example_watchexec::watcher::{FileChangeEvent, FileChangeKind};
+use examples_common::logging::{LogFormat, LogLevelFilter, Rotation};
 
 const CARGO_CRATE_NAME: &str = env!("CARGO_CRATE_NAME");
 const CARGO_MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
 const WATCHED_FILE_NAME: &str = "watched";
+/// If set, logs are also written to daily rolling files in this directory.
+const LOG_DIR_VAR: &str = "WATCHEXEC_LOG_DIR";
+/// How long to wait for further changes to a path before recording it
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
 
 /// Read/write storage available for use during response to [watchexec] events.
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct Runtime {
     /// A map of events that occurred historically
     event_history: BTreeMap<DateTime<Utc>, Vec<ChangeEvent>>,
     /// A count of events that have been processed
     event_count: usize,
+    /// A command to run through [watchexec] once the first event arrives
+    startup_command: Option<Arc<Command>>,
+    /// The running [watchexec] instance, used to report process completions back as events
+    watchexec: Weak<Watchexec>,
+    /// The window within which changes to the same path are coalesced into one
+    debounce: Duration,
+    /// File changes waiting out the debounce window, keyed by path
+    pending: HashMap<PathBuf, (Instant, FileChangeEvent)>,
+    /// Which paths' changes should be recorded at all
+    path_filter: PathFilter,
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Self {
+            event_history: Default::default(),
+            event_count: Default::default(),
+            startup_command: None,
+            watchexec: Weak::new(),
+            debounce: DEFAULT_DEBOUNCE,
+            pending: Default::default(),
+            path_filter: Default::default(),
+        }
+    }
 }
 
 impl Runtime {
     pub fn new() -> Arc<Mutex<Self>> {
-        Arc::new(Mutex::new(Default::default()))
+        Self::with_debounce(DEFAULT_DEBOUNCE)
+    }
+
+    /// Create a runtime which coalesces changes to the same path within the given window.
+    pub fn with_debounce(debounce: Duration) -> Arc<Mutex<Self>> {
+        Arc::new(Mutex::new(Self {
+            debounce,
+            ..Default::default()
+        }))
     }
 
     /// Process an event that occurred in [watchexec].
@@ -60,45 +101,42 @@ impl Runtime {
             }
         }
 
-        // then process file events
+        // then buffer file events until they've settled
         for event in action.events.iter() {
-            let mut file_path = Option::<PathBuf>::None;
-            let mut event_type = Option::<FileChangeKind>::None;
+            match FileChangeEvent::from_event(event) {
+                Some(change) if !self.path_filter.matches(&change.path) => {
+                    tracing::trace!(ts = ts.to_rfc3339(), path = %change.path.display(), "Ignoring file event for filtered path");
+                }
+                Some(change) => {
+                    tracing::debug!(ts = ts.to_rfc3339(), path = %change.path.display(), event_type = ?change.kind, "Received file event");
 
-            for tag in event.tags.iter() {
-                match tag {
-                    Tag::Path { path, .. } => {
-                        file_path = Some(path.clone());
-                    }
-                    Tag::FileEventKind(kind) => {
-                        event_type = match kind {
-                            FileEventKind::Access(_) => Some(FileChangeKind::Accessed),
-                            FileEventKind::Create(_) => Some(FileChangeKind::Created),
-                            FileEventKind::Modify(_) => Some(FileChangeKind::Modified),
-                            FileEventKind::Remove(_) => Some(FileChangeKind::Removed),
-                            _ => None
-                        };
-                    }
-                    _ => {}
+                    self.debounce(change);
+                }
+                None => {
+                    tracing::debug!(ts = ts.to_rfc3339(), "Not a file event, continuing");
                 }
             }
+        }
 
-            if file_path.is_none() || event_type.is_none() {
-                tracing::debug!(ts = ts.to_rfc3339(), "Not a file event, continuing");
-                continue;
-            } else {
-                let (file_path, event_type) = (file_path.unwrap(), event_type.unwrap());
+        // record changes which have outlasted the debounce window, or all of them if we're exiting
+        for change in self.flush(must_exit) {
+            events.push(ChangeEvent::FileChanged(change));
+        }
 
-                tracing::debug!(ts = ts.to_rfc3339(), path = %file_path.display(), event_type = ?event_type, "Received file event");
+        // then process completions of commands run through watchexec
+        for status in action.completions() {
+            tracing::info!(ts = ts.to_rfc3339(), status = ?status, "Process completed");
 
-                events.push(ChangeEvent::FileChanged(FileChangeEvent {
-                    path: file_path,
-                    kind: event_type,
-                }));
-            }
+            events.push(ChangeEvent::ProcessCompleted { status });
         }
 
-        // FIXME watchexec also makes it possible to monitor _processes_ and respond to their events
+        // run the startup command once; its completion will arrive as a later event
+        if let Some(command) = self.startup_command.take() {
+            tracing::debug!(ts = ts.to_rfc3339(), ?command, "Starting command");
+
+            let (_id, job) = action.create_job(command);
+            tokio::spawn(Self::monitor_job(job, self.watchexec.clone()));
+        }
 
         // record the events
         self.event_history.insert(ts, events);
@@ -111,38 +149,230 @@ impl Runtime {
         // return the action
         action
     }
+
+    /// Buffer a file change, coalescing it with any pending change to the same path.
+    fn debounce(&mut self, change: FileChangeEvent) {
+        match self.pending.get_mut(&change.path) {
+            Some((_, pending)) => {
+                // an access doesn't tell us anything new about a file which was otherwise changed
+                if change.kind != FileChangeKind::Accessed {
+                    pending.kind = change.kind;
+                }
+            }
+            None => {
+                self.pending.insert(change.path.clone(), (Instant::now(), change));
+
+                // wake the action handler once the window has passed so that the change is flushed
+                let (wx, debounce) = (self.watchexec.clone(), self.debounce);
+
+                tokio::spawn(async move {
+                    tokio::time::sleep(debounce).await;
+
+                    if let Some(wx) = wx.upgrade() {
+                        let _ = wx.send_event(Event::default(), Priority::Low).await;
+                    }
+                });
+            }
+        }
+    }
+
+    /// Take the pending file changes whose debounce window has passed, or all of them if `all`.
+    fn flush(&mut self, all: bool) -> Vec<FileChangeEvent> {
+        let now = Instant::now();
+
+        let settled: Vec<PathBuf> = self.pending.iter()
+            .filter(|(_, (first_seen, _))| all || now.duration_since(*first_seen) >= self.debounce)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        settled.into_iter()
+            .filter_map(|path| self.pending.remove(&path))
+            .map(|(_, change)| change)
+            .collect()
+    }
+
+    /// Summarize the events recorded so far.
+    pub fn report(&self) -> Report {
+        let mut report = Report {
+            actions: self.event_count,
+            ..Default::default()
+        };
+
+        for (ts, events) in self.event_history.iter().filter(|(_, events)| !events.is_empty()) {
+            report.first_event.get_or_insert(*ts);
+            report.last_event = Some(*ts);
+
+            for event in events {
+                match event {
+                    ChangeEvent::FileChanged(change) => {
+                        report.changes.entry(change.kind).or_default().add_assign(1);
+                    }
+                    ChangeEvent::SignalReceived(_) => report.signals.add_assign(1),
+                    ChangeEvent::ProcessCompleted { .. } => report.processes.add_assign(1),
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Start a job and, once it ends, send its exit status back into [watchexec] as a process
+    /// completion event.
+    ///
+    /// Watchexec doesn't emit completion events for jobs by itself, so this is how they make their
+    /// way into [Runtime::on_event].
+    async fn monitor_job(job: Job, wx: Weak<Watchexec>) {
+        job.start().await;
+        job.to_wait().await;
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        job.run(move |ctx| {
+            let status = match ctx.current {
+                CommandState::Finished { status, .. } => Some(*status),
+                _ => None,
+            };
+
+            let _ = tx.send(status);
+        })
+        .await;
+
+        let status = rx.await.ok().flatten();
+
+        if let Some(wx) = wx.upgrade() {
+            let event = Event {
+                tags: vec![Tag::Source(Source::Internal), Tag::ProcessCompletion(status)],
+                metadata: Default::default(),
+            };
+
+            if let Err(e) = wx.send_event(event, Priority::Low).await {
+                tracing::error!(error = %e, "Unable to send process completion event");
+            }
+        }
+    }
+}
+
+/// Include and exclude glob patterns deciding which paths' changes are recorded.
+///
+/// Patterns are matched against the full path, and `*` may cross directory boundaries, so `*.rs`
+/// matches Rust sources anywhere while `**/target/**` excludes everything under a `target`
+/// directory. With no include patterns, every path which isn't excluded is matched.
+#[derive(Debug, Default)]
+struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl PathFilter {
+    /// Compile a filter from the given include and exclude patterns.
+    pub fn new<I, E>(include: I, exclude: E) -> Result<Self, globset::Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+        E: IntoIterator,
+        E::Item: AsRef<str>,
+    {
+        Ok(Self {
+            include: Self::compile(include)?,
+            exclude: Self::compile(exclude)?,
+        })
+    }
+
+    /// Whether changes to the given path should be recorded.
+    pub fn matches(&self, path: &Path) -> bool {
+        let included = self.include.as_ref().is_none_or(|set| set.is_match(path));
+        let excluded = self.exclude.as_ref().is_some_and(|set| set.is_match(path));
+
+        included && !excluded
+    }
+
+    fn compile<P>(patterns: P) -> Result<Option<GlobSet>, globset::Error>
+    where
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+    {
+        let mut builder = GlobSetBuilder::new();
+        let mut empty = true;
+
+        for pattern in patterns {
+            builder.add(Glob::new(pattern.as_ref())?);
+            empty = false;
+        }
+
+        if empty {
+            return Ok(None);
+        }
+
+        builder.build().map(Some)
+    }
 }
 
 #[derive(Debug)]
 enum ChangeEvent {
     FileChanged(FileChangeEvent),
     SignalReceived(Signal),
+    /// A command run through watchexec completed; the status is unknown if [None].
+    #[allow(unused)]
+    ProcessCompleted { status: Option<ProcessEnd> },
 }
 
-#[derive(Debug)]
-struct FileChangeEvent {
-    #[allow(unused)]
-    kind: FileChangeKind,
-    #[allow(unused)]
-    path: PathBuf,
+/// A summary of the events recorded by a [Runtime].
+#[derive(Debug, Default)]
+struct Report {
+    /// The number of times the action handler was invoked
+    actions: usize,
+    /// The number of file changes recorded, by kind
+    changes: HashMap<FileChangeKind, usize>,
+    /// The number of signals received
+    signals: usize,
+    /// The number of completed processes
+    processes: usize,
+    /// When the first event was recorded, if any were
+    first_event: Option<DateTime<Utc>>,
+    /// When the last event was recorded, if any were
+    last_event: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug)]
-enum FileChangeKind {
-    Accessed,
-    Created,
-    Modified,
-    Removed,
+impl Report {
+    /// The number of file changes of the given kind.
+    pub fn changes(&self, kind: FileChangeKind) -> usize {
+        self.changes.get(&kind).copied().unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let timestamp = |ts: Option<DateTime<Utc>>| ts.map(|ts| ts.to_rfc3339()).unwrap_or_else(|| "-".into());
+
+        writeln!(f, "{:<12} {:>32}", "actions", self.actions)?;
+
+        for kind in [FileChangeKind::Accessed, FileChangeKind::Created, FileChangeKind::Modified, FileChangeKind::Removed] {
+            writeln!(f, "{:<12} {:>32}", format!("{kind:?}").to_lowercase(), self.changes(kind))?;
+        }
+
+        writeln!(f, "{:<12} {:>32}", "signals", self.signals)?;
+        writeln!(f, "{:<12} {:>32}", "processes", self.processes)?;
+        writeln!(f, "{:<12} {:>32}", "first event", timestamp(self.first_event))?;
+        write!(f, "{:<12} {:>32}", "last event", timestamp(self.last_event))
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // initialize logging
-    examples_common::logging::init_logging(LogLevelFilter::builder()
+    let mut filter = LogLevelFilter::builder()
         .global(Level::WARN)
         .level(CARGO_CRATE_NAME, Level::TRACE)
         .level(examples_common::CRATE_NAME, Level::DEBUG)
-        .build());
+        .rate_limit(20);
+
+    if let Ok(dir) = std::env::var(LOG_DIR_VAR) {
+        filter = filter.rolling_file(dir, CARGO_CRATE_NAME, Rotation::DAILY);
+    }
+
+    // keep the guard alive so that buffered file output is flushed on exit
+    let (_handle, _log_guard) =
+        examples_common::logging::init_logging_guarded(filter.build(), LogFormat::Pretty);
 
 
     // start work
@@ -159,6 +389,17 @@ async fn main() -> Result<()> {
 
     // runtime handle to be passed into the executor
     let rt = Runtime::new();
+
+    // demonstrate process monitoring by running a short command once watchexec is up
+    rt.lock().await.startup_command = Some(Arc::new(Command {
+        program: Program::Exec {
+            prog: "echo".into(),
+            args: vec![format!("watching {}", w.display())],
+        },
+        options: Default::default(),
+    }));
+    // ignore editor swap and backup files
+    rt.lock().await.path_filter = PathFilter::new(None::<&str>, ["*.swp", "*~"])?;
     // runtime handle to remain outside of the executor
     let rt_handle = rt.clone();
 
@@ -188,14 +429,211 @@ async fn main() -> Result<()> {
         })
     })?;
 
+    rt_handle.lock().await.watchexec = Arc::downgrade(&wx);
+
     wx.config.pathset([w]);
-    wx.main().await??;
 
-    let total_events: usize = {
-        rt_handle.lock().await.event_history.values().map(|v| v.len()).sum()
-    };
+    let main = wx.main();
+
+    // send an empty event to kick off the startup command
+    wx.send_event(Event::default(), Priority::Urgent).await?;
+
+    main.await??;
+
+    let report = rt_handle.lock().await.report();
 
-    tracing::info!(events = rt_handle.lock().await.event_count, total_events, "Shutting down");
+    tracing::info!("Shutting down, event summary:\n{report}");
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChangeEvent, PathFilter, Runtime};
+
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use chrono::{TimeZone, Utc};
+    use example_watchexec::watcher::{FileChangeEvent, FileChangeKind};
+    use watchexec_signals::Signal;
+    use tokio::sync::Mutex;
+    use watchexec::command::{Command, Program};
+    use watchexec::Watchexec;
+    use watchexec_events::{Event, Priority, ProcessEnd};
+
+    /// Create a [Watchexec] instance dispatching to the given runtime.
+    async fn watchexec(rt: &Arc<Mutex<Runtime>>) -> Arc<Watchexec> {
+        let wx = {
+            let rt = rt.clone();
+
+            Watchexec::new_async(move |action| {
+                let rt = rt.clone();
+
+                Box::new(async move { rt.lock().await.on_event(action).await })
+            })
+            .unwrap()
+        };
+
+        rt.lock().await.watchexec = Arc::downgrade(&wx);
+
+        wx
+    }
+
+    fn modified_count(rt: &Runtime) -> usize {
+        rt.event_history
+            .values()
+            .flatten()
+            .filter(|e| matches!(e, ChangeEvent::FileChanged(c) if c.kind == FileChangeKind::Modified))
+            .count()
+    }
+
+    #[tokio::test]
+    async fn test_process_completion_recorded() {
+        let rt = Runtime::new();
+
+        rt.lock().await.startup_command = Some(Arc::new(Command {
+            program: Program::Exec {
+                prog: "true".into(),
+                args: Vec::new(),
+            },
+            options: Default::default(),
+        }));
+
+        let wx = watchexec(&rt).await;
+
+        let main = wx.main();
+        wx.send_event(Event::default(), Priority::Urgent).await.unwrap();
+
+        let status = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let completed = rt.lock().await.event_history.values().flatten().find_map(|e| match e {
+                    ChangeEvent::ProcessCompleted { status } => Some(*status),
+                    _ => None,
+                });
+
+                if let Some(status) = completed {
+                    return status;
+                }
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("no process completion recorded");
+
+        assert_eq!(Some(ProcessEnd::Success), status);
+
+        main.abort();
+    }
+
+    #[tokio::test]
+    async fn test_rapid_writes_debounced() {
+        let dir = tempfile::tempdir().expect("unable to create temp dir");
+        let path = dir.path().join("watched");
+
+        tokio::fs::write(&path, "init").await.unwrap();
+
+        let debounce = Duration::from_millis(300);
+        let rt = Runtime::with_debounce(debounce);
+        let wx = watchexec(&rt).await;
+
+        wx.config.pathset([path.clone()]);
+
+        let main = wx.main();
+
+        // give the watcher a moment to register with the filesystem
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        for content in ["one", "two", "three"] {
+            tokio::fs::write(&path, content).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(15)).await;
+        }
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while modified_count(&*rt.lock().await) == 0 {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("no modify event recorded");
+
+        // wait out another window to make sure nothing else trickles in
+        tokio::time::sleep(debounce * 2).await;
+
+        assert_eq!(1, modified_count(&*rt.lock().await));
+
+        main.abort();
+    }
+
+    #[tokio::test]
+    async fn test_path_filter_include() {
+        let dir = tempfile::tempdir().expect("unable to create temp dir");
+        let (ignored, included) = (dir.path().join("scratch.tmp"), dir.path().join("main.rs"));
+
+        let rt = Runtime::new();
+        rt.lock().await.path_filter = PathFilter::new(["*.rs"], None::<&str>).unwrap();
+
+        let wx = watchexec(&rt).await;
+
+        wx.config.pathset([dir.path().to_path_buf()]);
+
+        let main = wx.main();
+
+        // give the watcher a moment to register with the filesystem
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        tokio::fs::write(&ignored, "ignored").await.unwrap();
+        tokio::fs::write(&included, "fn main() {}").await.unwrap();
+
+        let paths = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let paths: Vec<_> = rt.lock().await.event_history.values().flatten().filter_map(|e| match e {
+                    ChangeEvent::FileChanged(c) => Some(c.path.clone()),
+                    _ => None,
+                }).collect();
+
+                if paths.contains(&included) {
+                    return paths;
+                }
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("no change to the included file recorded");
+
+        assert!(!paths.contains(&ignored), "change to filtered path was recorded");
+
+        main.abort();
+    }
+
+    #[test]
+    fn test_report_counts() {
+        let change = |kind| ChangeEvent::FileChanged(FileChangeEvent { kind, path: PathBuf::from("watched") });
+        let (first, last) = (Utc.timestamp_opt(1, 0).unwrap(), Utc.timestamp_opt(3, 0).unwrap());
+
+        let mut rt = Runtime {
+            event_count: 4,
+            ..Default::default()
+        };
+
+        rt.event_history.insert(first, vec![change(FileChangeKind::Created), change(FileChangeKind::Modified)]);
+        rt.event_history.insert(Utc.timestamp_opt(2, 0).unwrap(), vec![change(FileChangeKind::Modified)]);
+        rt.event_history.insert(last, vec![change(FileChangeKind::Removed), ChangeEvent::SignalReceived(Signal::Interrupt)]);
+        // empty entries, such as debounce wake-ups, don't count towards the timestamps
+        rt.event_history.insert(Utc.timestamp_opt(4, 0).unwrap(), Vec::new());
+
+        let report = rt.report();
+
+        assert_eq!(4, report.actions);
+        assert_eq!(0, report.changes(FileChangeKind::Accessed));
+        assert_eq!(1, report.changes(FileChangeKind::Created));
+        assert_eq!(2, report.changes(FileChangeKind::Modified));
+        assert_eq!(1, report.changes(FileChangeKind::Removed));
+        assert_eq!(1, report.signals);
+        assert_eq!(0, report.processes);
+        assert_eq!(Some(first), report.first_event);
+        assert_eq!(Some(last), report.last_event);
+    }
+}
@@ -1,2 +1,4 @@
+pub mod watcher;
+
 /// Convenience constant for the crate name
-pub const CRATE_NAME: &str = env!("CARGO_CRATE_NAME");
\ No newline at end of file
+pub const CRATE_NAME: &str = env!("CARGO_CRATE_NAME");
@@ -0,0 +1,183 @@
+//! A reusable file watcher built on [watchexec], hiding the [ActionHandler] plumbing.
+
+#[cfg(test)]
+mod tests;
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use anyhow::Result;
+use tokio::task::JoinHandle;
+use watchexec::action::ActionHandler;
+use watchexec::error::CriticalError;
+use watchexec::Watchexec;
+use watchexec_events::filekind::FileEventKind;
+use watchexec_events::{Event, Priority, Tag};
+use watchexec_signals::Signal;
+
+/// A callback invoked for every file change observed by a [FileWatcher].
+type ChangeCallback = Arc<dyn Fn(&FileChangeEvent) + Send + Sync>;
+
+/// A change to a file on disk.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FileChangeEvent {
+    pub kind: FileChangeKind,
+    pub path: PathBuf,
+}
+
+impl FileChangeEvent {
+    /// Extract a file change from a [watchexec] event, if it carries both a path and a file event
+    /// kind which we're interested in.
+    pub fn from_event(event: &Event) -> Option<Self> {
+        let mut path = Option::<PathBuf>::None;
+        let mut kind = Option::<FileChangeKind>::None;
+
+        for tag in event.tags.iter() {
+            match tag {
+                Tag::Path { path: p, .. } => {
+                    path = Some(p.clone());
+                }
+                Tag::FileEventKind(k) => {
+                    kind = match k {
+                        FileEventKind::Access(_) => Some(FileChangeKind::Accessed),
+                        FileEventKind::Create(_) => Some(FileChangeKind::Created),
+                        FileEventKind::Modify(_) => Some(FileChangeKind::Modified),
+                        FileEventKind::Remove(_) => Some(FileChangeKind::Removed),
+                        _ => None,
+                    };
+                }
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            kind: kind?,
+            path: path?,
+        })
+    }
+}
+
+/// The kind of change made to a file.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum FileChangeKind {
+    Accessed,
+    Created,
+    Modified,
+    Removed,
+}
+
+/// Watches a set of paths, invoking callbacks for each file change.
+pub struct FileWatcher {
+    paths: Vec<PathBuf>,
+    callbacks: Vec<ChangeCallback>,
+    /// Callbacks only invoked for changes at or beneath their path.
+    path_callbacks: Vec<(PathBuf, ChangeCallback)>,
+}
+
+impl FileWatcher {
+    /// Create a watcher over the given paths.
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            paths: paths.into_iter().collect(),
+            callbacks: Vec::new(),
+            path_callbacks: Vec::new(),
+        }
+    }
+
+    /// Register a callback to be invoked with each file change.
+    pub fn on_change<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&FileChangeEvent) + Send + Sync + 'static,
+    {
+        self.callbacks.push(Arc::new(callback));
+        self
+    }
+
+    /// Watch an additional path, registering a callback to be invoked only with changes at or
+    /// beneath it, e.g. to handle several project subtrees differently.
+    ///
+    /// Changes are matched against the path as given, so it should be spelled the same way as the
+    /// paths reported by the filesystem, i.e. absolute. Callbacks registered with
+    /// [FileWatcher::on_change] still receive every change, and a change beneath several watched
+    /// paths is dispatched to each of their callbacks.
+    pub fn watch<P, F>(mut self, path: P, callback: F) -> Self
+    where
+        P: Into<PathBuf>,
+        F: Fn(&FileChangeEvent) + Send + Sync + 'static,
+    {
+        let path = path.into();
+
+        self.paths.push(path.clone());
+        self.path_callbacks.push((path, Arc::new(callback)));
+        self
+    }
+
+    /// Start watching in the background, returning a handle which can be used to stop.
+    ///
+    /// Watching also stops when the process receives an interrupt or terminate signal.
+    pub fn start(self) -> Result<FileWatcherHandle> {
+        let stopping = Arc::new(AtomicBool::new(false));
+
+        let wx = {
+            let (callbacks, path_callbacks, stopping) =
+                (self.callbacks, self.path_callbacks, stopping.clone());
+
+            Watchexec::new(move |mut action: ActionHandler| {
+                let signalled = action
+                    .signals()
+                    .any(|s| s == Signal::Interrupt || s == Signal::Terminate);
+
+                for change in action.events.iter().filter_map(FileChangeEvent::from_event) {
+                    tracing::debug!(path = %change.path.display(), kind = ?change.kind, "Received file event");
+
+                    for callback in callbacks.iter() {
+                        callback(&change);
+                    }
+
+                    for (_, callback) in path_callbacks
+                        .iter()
+                        .filter(|(path, _)| change.path.starts_with(path))
+                    {
+                        callback(&change);
+                    }
+                }
+
+                if signalled || stopping.load(Ordering::Acquire) {
+                    action.quit();
+                }
+
+                action
+            })?
+        };
+
+        wx.config.pathset(self.paths);
+
+        let task = wx.main();
+
+        Ok(FileWatcherHandle { wx, task, stopping })
+    }
+}
+
+/// A handle to a running [FileWatcher].
+pub struct FileWatcherHandle {
+    wx: Arc<Watchexec>,
+    task: JoinHandle<Result<(), CriticalError>>,
+    stopping: Arc<AtomicBool>,
+}
+
+impl FileWatcherHandle {
+    /// Stop watching and wait for the watcher to shut down.
+    pub async fn stop(self) -> Result<()> {
+        self.stopping.store(true, Ordering::Release);
+
+        // wake the action handler so that it notices we're stopping
+        self.wx
+            .send_event(Event::default(), Priority::Urgent)
+            .await?;
+
+        self.task.await??;
+
+        Ok(())
+    }
+}
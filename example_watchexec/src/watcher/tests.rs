@@ -0,0 +1,104 @@
+use super::{FileChangeKind, FileWatcher};
+
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn test_modify_triggers_callback() {
+    let dir = tempfile::tempdir().expect("unable to create temp dir");
+    let path = dir.path().join("watched");
+
+    tokio::fs::write(&path, "init").await.unwrap();
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    let handle = FileWatcher::new([path.clone()])
+        .on_change(move |change| {
+            let _ = tx.send(change.clone());
+        })
+        .start()
+        .expect("unable to start watcher");
+
+    // give the watcher a moment to register with the filesystem
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    tokio::fs::write(&path, "modified").await.unwrap();
+
+    let change = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let change = rx.recv().await.expect("watcher hung up");
+
+            if change.kind == FileChangeKind::Modified {
+                return change;
+            }
+        }
+    })
+    .await
+    .expect("no modify event received");
+
+    assert_eq!(path, change.path);
+
+    handle.stop().await.expect("unable to stop watcher");
+}
+
+#[tokio::test]
+async fn test_per_path_callbacks() {
+    let (first, second) = (
+        tempfile::tempdir().expect("unable to create temp dir"),
+        tempfile::tempdir().expect("unable to create temp dir"),
+    );
+
+    let (first_tx, mut first_rx) = mpsc::unbounded_channel();
+    let (second_tx, mut second_rx) = mpsc::unbounded_channel();
+
+    let handle = FileWatcher::new([])
+        .watch(first.path(), move |change| {
+            let _ = first_tx.send(change.clone());
+        })
+        .watch(second.path(), move |change| {
+            let _ = second_tx.send(change.clone());
+        })
+        .start()
+        .expect("unable to start watcher");
+
+    // give the watcher a moment to register with the filesystem
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let (first_file, second_file) = (first.path().join("one"), second.path().join("two"));
+
+    tokio::fs::write(&first_file, "first").await.unwrap();
+    tokio::fs::write(&second_file, "second").await.unwrap();
+
+    // neither callback may see the other's changes
+    let watched = [
+        (&mut first_rx, first.path(), &first_file),
+        (&mut second_rx, second.path(), &second_file),
+    ];
+
+    for (rx, dir, file) in watched {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let change = rx.recv().await.expect("watcher hung up");
+
+                assert!(change.path.starts_with(dir), "{change:?} outside {dir:?}");
+
+                if change.path == *file {
+                    break;
+                }
+            }
+        })
+        .await
+        .expect("no event received for watched path");
+    }
+
+    // wait for anything else to trickle in before stopping
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    handle.stop().await.expect("unable to stop watcher");
+
+    while let Ok(change) = first_rx.try_recv() {
+        assert!(change.path.starts_with(first.path()), "{change:?}");
+    }
+
+    while let Ok(change) = second_rx.try_recv() {
+        assert!(change.path.starts_with(second.path()), "{change:?}");
+    }
+}
@@ -1,6 +1,6 @@
 use tracing::Level;
 
-use examples_common::logging::LogLevelFilter;
+use examples_common::logging::{LogFormat, LogLevelFilter};
 
 fn main() {
     // setup logging
@@ -10,7 +10,8 @@ fn main() {
         .level(env!("CARGO_CRATE_NAME"), Level::TRACE)
         // set the logging for the examples_common crate
         .level(examples_common::CRATE_NAME, Level::TRACE)
-        .build()
+        .build(),
+        LogFormat::Pretty,
     );
 
     // test the crate
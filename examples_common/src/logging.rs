@@ -1,19 +1,49 @@
 //! Logging utilities.
 
-use std::collections::HashMap;
-use std::io;
+#[cfg(test)]
+mod tests;
+
+use std::any::TypeId;
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeSet, HashMap};
+use std::env;
+use std::fmt;
+use std::hash::BuildHasher;
+use std::io::{self, IsTerminal};
 use std::iter::successors;
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::Instant;
 
-use parking_lot::Once;
-use tracing::{Level, Metadata};
-use tracing_subscriber::{filter, Layer, Registry};
-use tracing_subscriber::layer::SubscriberExt;
+use thiserror::Error;
+use tracing::field::{self, DisplayValue, Field, Value, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::{DefaultGuard, Interest};
+use tracing::{Dispatch, Event, Level, Metadata, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::RollingFileAppender;
+use tracing_subscriber::fmt::format::{self, Writer};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter};
+use tracing_subscriber::layer::{Context, Filter, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{reload, Layer, Registry};
 
 const DEFAULT_LEVEL: Level = Level::WARN;
 const DEFAULT_CRATE_LEVEL: Level = Level::DEBUG;
 
-static LOGGING_INIT: Once = Once::new();
+/// The value written in place of a redacted field, see [LogLevelFilterBuilder::redact].
+pub const REDACTED: &str = "***";
+
+/// The most fields an event can have, a limit imposed by [tracing]'s macros.
+const MAX_EVENT_FIELDS: usize = 32;
+
+/// Disables colored output when set to a non-empty value, see <https://no-color.org>.
+const NO_COLOR_VAR: &str = "NO_COLOR";
+
+static LOGGING_HANDLE: OnceLock<ReloadHandle> = OnceLock::new();
 
 /// Emit logs at all levels to test logging.
 #[macro_export]
@@ -28,14 +58,111 @@ macro_rules! log_level_test {
 }
 
 pub use log_level_test;
+pub use tracing_appender::rolling::Rotation;
+
+/// The output format of log events.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum LogFormat {
+    /// Multi-line, human-readable output.
+    #[default]
+    Pretty,
+    /// Single-line, human-readable output.
+    Compact,
+    /// Newline-delimited JSON, for consumption by log aggregators.
+    Json,
+}
+
+/// Initialize logging to stderr idempotently, returning a handle for changing levels at runtime.
+///
+/// Calling this more than once will have no effect besides returning the existing handle.
+///
+/// If a [rolling file](LogLevelFilterBuilder::rolling_file) is configured, it's written to for the
+/// life of the process, but events still buffered at exit may be lost; use [init_logging_guarded]
+/// to flush them.
+pub fn init_logging(filter: LogLevelFilter, format: LogFormat) -> ReloadHandle {
+    let (handle, guard) = init_logging_guarded(filter, format);
+
+    // keep the file writer running for the life of the process
+    std::mem::forget(guard);
+
+    handle
+}
+
+/// Initialize logging to stderr idempotently as in [init_logging], also returning the guard of the
+/// [rolling file](LogLevelFilterBuilder::rolling_file) writer, if one is configured.
+///
+/// Events are written to the file on a background thread, which stops once the guard is dropped,
+/// flushing any buffered events; keep it alive until the end of `main`. The guard is only returned
+/// by the call which initializes logging.
+pub fn init_logging_guarded(
+    filter: LogLevelFilter,
+    format: LogFormat,
+) -> (ReloadHandle, Option<WorkerGuard>) {
+    init_logging_with_writer(filter, format, io::stderr)
+}
+
+/// Initialize logging idempotently as in [init_logging_guarded], but writing to the given writer
+/// rather than stderr, e.g. to capture output in a buffer or write to a file.
+pub fn init_logging_with_writer<W>(
+    filter: LogLevelFilter,
+    format: LogFormat,
+    writer: W,
+) -> (ReloadHandle, Option<WorkerGuard>)
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let mut guard = None;
+
+    let handle = LOGGING_HANDLE
+        .get_or_init(|| {
+            let (handle, file_guard) = init_logging_actual(filter, format, writer);
+            guard = file_guard;
+            handle
+        })
+        .clone();
+
+    (handle, guard)
+}
+
+/// Install logging to stderr for the current thread only, until the returned guard is dropped.
+///
+/// Unlike [init_logging], this can be called any number of times: dropping the guard restores the
+/// previously installed subscriber, so each test can install its own filter without interfering
+/// with others.
+pub fn init_logging_scoped(filter: LogLevelFilter, format: LogFormat) -> ScopedLogging {
+    init_logging_scoped_with_writer(filter, format, io::stderr)
+}
+
+/// Install logging for the current thread as in [init_logging_scoped], but writing to the given
+/// writer rather than stderr.
+pub fn init_logging_scoped_with_writer<W>(
+    filter: LogLevelFilter,
+    format: LogFormat,
+    writer: W,
+) -> ScopedLogging
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let (subscriber, _handle, file_guard) = subscriber(filter, format, writer);
+
+    ScopedLogging {
+        _default: tracing::subscriber::set_default(subscriber),
+        _file: file_guard,
+    }
+}
 
-/// Initialize logging idempotently.
+/// Capture events at all levels from every module for the current thread, until the returned
+/// handle is dropped, e.g. to assert on log output in tests.
 ///
-/// Calling this more than once will have no effect.
-pub fn init_logging(filter: LogLevelFilter) {
-    let filter = Arc::new(filter);
+/// As with [init_logging_scoped], this can be called any number of times.
+pub fn init_logging_capture() -> CaptureHandle {
+    let layer = CapturingLayer::new();
+    let guard = tracing::subscriber::set_default(Registry::default().with(layer.clone()));
 
-    LOGGING_INIT.call_once(move || init_logging_actual(filter));
+    CaptureHandle {
+        layer,
+        _guard: guard,
+    }
 }
 
 /// Test logging in this crate by emitting events at all log levels.
@@ -44,21 +171,648 @@ pub fn self_log_test() {
     log_level_test!();
 }
 
-fn init_logging_actual(filter: Arc<LogLevelFilter>) {
-    tracing::subscriber::set_global_default(
-        Registry::default().with(
-            tracing_subscriber::fmt::layer()
-                .with_writer(io::stderr)
-                .pretty()
-                .with_filter(filter::filter_fn(move |meta| filter.allow(meta))),
-        ),
-    )
-        .unwrap();
+fn init_logging_actual<W>(
+    filter: LogLevelFilter,
+    format: LogFormat,
+    writer: W,
+) -> (ReloadHandle, Option<WorkerGuard>)
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let (subscriber, handle, guard) = subscriber(filter, format, writer);
+
+    tracing::subscriber::set_global_default(subscriber).unwrap();
+
+    (handle, guard)
+}
+
+/// Build the subscriber used for logging, along with a handle for reloading its filter and the
+/// guard of the rolling file writer, if one is configured.
+fn subscriber<W>(
+    mut filter: LogLevelFilter,
+    format: LogFormat,
+    writer: W,
+) -> (
+    impl Subscriber + Send + Sync,
+    ReloadHandle,
+    Option<WorkerGuard>,
+)
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    // the context, trace ids, and file are fixed for the lifetime of the subscriber, so they don't
+    // live on in the filter
+    let context = std::mem::take(&mut filter.context);
+    let trace_ids = filter.trace_ids;
+    let ansi = filter.ansi;
+    let redact = std::mem::take(&mut filter.redact);
+    let file = filter.file.take();
+
+    let (filter, handle) = reload::Layer::new(filter);
+
+    // the file gets the same format as the primary writer, minus the terminal colors
+    let (file_layer, guard) = match file {
+        Some(file) => {
+            let (writer, guard) = tracing_appender::non_blocking(file.appender());
+            let layer = tracing_subscriber::fmt::layer()
+                .with_writer(writer)
+                .with_ansi(false);
+
+            (
+                Some(boxed_fmt_layer(layer, format, context.clone(), trace_ids)),
+                Some(guard),
+            )
+        }
+        None => (None, None),
+    };
+
+    let layer = boxed_fmt_layer(
+        tracing_subscriber::fmt::layer()
+            .with_writer(writer)
+            .with_ansi(ansi),
+        format,
+        context,
+        trace_ids,
+    );
+
+    let subscriber = Registry::default().with(
+        RedactingLayer::new(layer.and_then(file_layer), redact)
+            .and_then(trace_ids.then(TraceIdLayer::new))
+            .with_filter(filter),
+    );
+
+    (subscriber, ReloadHandle { inner: handle }, guard)
+}
+
+/// Apply the given format, context, and trace id fields to a fmt layer, boxing the result.
+fn boxed_fmt_layer<W>(
+    layer: tracing_subscriber::fmt::Layer<Registry, format::DefaultFields, format::Format, W>,
+    format: LogFormat,
+    context: Vec<(String, String)>,
+    trace_ids: bool,
+) -> Box<dyn Layer<Registry> + Send + Sync>
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Pretty => layer
+            .pretty()
+            .event_format(ContextFormat::text(
+                format::format().pretty(),
+                context,
+                trace_ids,
+            ))
+            .boxed(),
+        LogFormat::Compact => layer
+            .compact()
+            .event_format(ContextFormat::text(
+                format::format().compact(),
+                context,
+                trace_ids,
+            ))
+            .boxed(),
+        // flatten the event so that `message` sits alongside `level` at the top level
+        LogFormat::Json => layer
+            .json()
+            .event_format(ContextFormat::json(
+                format::format().json().flatten_event(true),
+                context,
+                trace_ids,
+            ))
+            .boxed(),
+    }
+}
+
+/// Formats events with the wrapped formatter, adding the global context fields configured with
+/// [LogLevelFilterBuilder::with_context] to every line, followed by the `trace_id` and `span_id`
+/// of the enclosing span if [LogLevelFilterBuilder::trace_ids] is enabled.
+///
+/// Events can't be given extra fields once they've been emitted, so rather than a layer recording
+/// fields, the context is written out alongside each formatted event.
+struct ContextFormat<F> {
+    inner: F,
+    context: Vec<(String, String)>,
+    trace_ids: bool,
+    json: bool,
+}
+
+impl<F> ContextFormat<F> {
+    /// Prefix each event with the context as `key=value` pairs.
+    fn text(inner: F, context: Vec<(String, String)>, trace_ids: bool) -> Self {
+        Self {
+            inner,
+            context,
+            trace_ids,
+            json: false,
+        }
+    }
+
+    /// Add the context as the leading fields of each JSON object.
+    fn json(inner: F, context: Vec<(String, String)>, trace_ids: bool) -> Self {
+        Self {
+            inner,
+            context,
+            trace_ids,
+            json: true,
+        }
+    }
+}
+
+impl<S, N, F> FormatEvent<S, N> for ContextFormat<F>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    F: FormatEvent<S, N>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let ids = self
+            .trace_ids
+            .then(|| ctx.parent_span())
+            .flatten()
+            .and_then(|span| span.extensions().get::<TraceIds>().copied())
+            .map(|ids| ids.fields());
+
+        let fields: Vec<(&str, &str)> = self
+            .context
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .chain(
+                ids.iter()
+                    .flatten()
+                    .map(|(key, value)| (*key, value.as_str())),
+            )
+            .collect();
+
+        if fields.is_empty() {
+            return self.inner.format_event(ctx, writer, event);
+        }
+
+        if !self.json {
+            for (key, value) in fields.iter() {
+                write!(writer, "{key}={value} ")?;
+            }
+
+            return self.inner.format_event(ctx, writer, event);
+        }
+
+        let mut line = String::new();
+        self.inner
+            .format_event(ctx, Writer::new(&mut line), event)?;
+
+        // splice the context in directly after the opening brace of the object
+        let rest = line.strip_prefix('{').ok_or(fmt::Error)?;
+
+        writer.write_char('{')?;
+
+        for (key, value) in fields.iter() {
+            let (key, value) = (
+                serde_json::to_string(key).map_err(|_| fmt::Error)?,
+                serde_json::to_string(value).map_err(|_| fmt::Error)?,
+            );
+
+            write!(writer, "{key}:{value},")?;
+        }
+
+        writer.write_str(rest)
+    }
+}
+
+/// OpenTelemetry-style ids of a span, stored in its extensions by [TraceIdLayer].
+#[derive(Debug, Clone, Copy)]
+struct TraceIds {
+    /// Shared by every span descended from the same root span.
+    trace_id: u128,
+    /// Unique to this span.
+    span_id: u64,
+}
+
+impl TraceIds {
+    /// The ids as fields, formatted as fixed-width lowercase hex as in the W3C trace context.
+    fn fields(self) -> [(&'static str, String); 2] {
+        [
+            ("trace_id", format!("{:032x}", self.trace_id)),
+            ("span_id", format!("{:016x}", self.span_id)),
+        ]
+    }
+}
+
+/// A layer assigning [TraceIds] to each span as it's created, so that events can be correlated
+/// with traces: root spans start a new trace, and child spans inherit the trace of their parent.
+///
+/// Ids only need to be unique rather than unpredictable, so they're derived by hashing a counter
+/// with a randomly keyed hasher rather than pulling in a random number generator.
+struct TraceIdLayer {
+    hasher: RandomState,
+    counter: AtomicU64,
+}
+
+impl TraceIdLayer {
+    fn new() -> Self {
+        Self {
+            hasher: RandomState::new(),
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    fn next_id(&self) -> u64 {
+        self.hasher
+            .hash_one(self.counter.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl<S> Layer<S> for TraceIdLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let trace_id = span
+            .parent()
+            .and_then(|parent| {
+                parent
+                    .extensions()
+                    .get::<TraceIds>()
+                    .map(|ids| ids.trace_id)
+            })
+            .unwrap_or_else(|| u128::from(self.next_id()) << 64 | u128::from(self.next_id()));
+
+        span.extensions_mut().insert(TraceIds {
+            trace_id,
+            span_id: self.next_id(),
+        });
+    }
+}
+
+/// Wraps a layer, replacing the values of event fields with any of the configured names with
+/// [REDACTED] before the wrapped layer sees them, so that secrets such as passwords never make it
+/// into the output.
+///
+/// Events are immutable once emitted, so an event with a field to redact is recorded and
+/// re-created with the redacted values. Events without any such field are passed through as-is.
+struct RedactingLayer<L> {
+    inner: L,
+    fields: BTreeSet<String>,
+}
+
+impl<L> RedactingLayer<L> {
+    fn new(inner: L, fields: BTreeSet<String>) -> Self {
+        Self { inner, fields }
+    }
+}
+
+impl<S, L> Layer<S> for RedactingLayer<L>
+where
+    S: Subscriber,
+    L: Layer<S>,
+{
+    fn on_register_dispatch(&self, subscriber: &Dispatch) {
+        self.inner.on_register_dispatch(subscriber);
+    }
+
+    fn on_layer(&mut self, subscriber: &mut S) {
+        self.inner.on_layer(subscriber);
+    }
+
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        self.inner.register_callsite(metadata)
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<'_, S>) -> bool {
+        self.inner.enabled(metadata, ctx)
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        self.inner.on_new_span(attrs, id, ctx);
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        self.inner.max_level_hint()
+    }
+
+    fn on_record(&self, span: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        self.inner.on_record(span, values, ctx);
+    }
+
+    fn on_follows_from(&self, span: &Id, follows: &Id, ctx: Context<'_, S>) {
+        self.inner.on_follows_from(span, follows, ctx);
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, ctx: Context<'_, S>) -> bool {
+        self.inner.event_enabled(event, ctx)
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if self.fields.is_empty() {
+            return self.inner.on_event(event, ctx);
+        }
+
+        let mut visitor = RedactVisitor {
+            names: &self.fields,
+            values: Vec::new(),
+            redacted: false,
+        };
+        event.record(&mut visitor);
+
+        let Some((filler, _)) = visitor.values.first().filter(|_| visitor.redacted) else {
+            return self.inner.on_event(event, ctx);
+        };
+
+        // a value set must be built from an array, so pad it out with empty values
+        let mut values: [(&Field, Option<&dyn Value>); MAX_EVENT_FIELDS] =
+            [(filler, None); MAX_EVENT_FIELDS];
+
+        for (slot, (field, value)) in values.iter_mut().zip(visitor.values.iter()) {
+            *slot = (field, Some(value.as_value()));
+        }
+
+        let metadata = event.metadata();
+        let values = metadata.fields().value_set(&values);
+
+        // keep the original event's parent, whether explicit, contextual, or none at all
+        let redacted = if event.is_contextual() {
+            Event::new(metadata, &values)
+        } else {
+            Event::new_child_of(event.parent().cloned(), metadata, &values)
+        };
+
+        self.inner.on_event(&redacted, ctx);
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        self.inner.on_enter(id, ctx);
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        self.inner.on_exit(id, ctx);
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        self.inner.on_close(id, ctx);
+    }
+
+    fn on_id_change(&self, old: &Id, new: &Id, ctx: Context<'_, S>) {
+        self.inner.on_id_change(old, new, ctx);
+    }
+
+    unsafe fn downcast_raw(&self, id: TypeId) -> Option<*const ()> {
+        if id == TypeId::of::<Self>() {
+            return Some(self as *const _ as *const ());
+        }
+
+        // SAFETY: forwarded as-is to the wrapped layer, which upholds the contract itself
+        unsafe { self.inner.downcast_raw(id) }
+    }
+}
+
+/// An owned copy of an event field's value, so that the event can be re-created.
+enum RecordedValue {
+    Str(String),
+    /// Values recorded by their [fmt::Debug] representation, such as the message, kept formatted.
+    Debug(DisplayValue<String>),
+    I64(i64),
+    U64(u64),
+    I128(i128),
+    U128(u128),
+    F64(f64),
+    Bool(bool),
+}
+
+impl RecordedValue {
+    fn as_value(&self) -> &dyn Value {
+        match self {
+            RecordedValue::Str(value) => value,
+            RecordedValue::Debug(value) => value,
+            RecordedValue::I64(value) => value,
+            RecordedValue::U64(value) => value,
+            RecordedValue::I128(value) => value,
+            RecordedValue::U128(value) => value,
+            RecordedValue::F64(value) => value,
+            RecordedValue::Bool(value) => value,
+        }
+    }
+}
+
+/// Records every field of an event, substituting [REDACTED] for the values of the named fields.
+struct RedactVisitor<'a> {
+    names: &'a BTreeSet<String>,
+    values: Vec<(Field, RecordedValue)>,
+    /// Whether any field was redacted.
+    redacted: bool,
+}
+
+impl RedactVisitor<'_> {
+    fn push(&mut self, field: &Field, value: RecordedValue) {
+        let value = if self.names.contains(field.name()) {
+            self.redacted = true;
+            RecordedValue::Str(REDACTED.to_string())
+        } else {
+            value
+        };
+
+        self.values.push((field.clone(), value));
+    }
+}
+
+impl Visit for RedactVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.push(field, RecordedValue::F64(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.push(field, RecordedValue::I64(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.push(field, RecordedValue::U64(value));
+    }
+
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        self.push(field, RecordedValue::I128(value));
+    }
+
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        self.push(field, RecordedValue::U128(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.push(field, RecordedValue::Bool(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, RecordedValue::Str(value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.push(
+            field,
+            RecordedValue::Debug(field::display(format!("{value:?}"))),
+        );
+    }
+}
+
+/// A layer recording each event as a line of text, such as `INFO my_crate: message key=value`, in
+/// a shared buffer which can be inspected after the fact.
+///
+/// Clones share the same buffer.
+#[derive(Clone, Default)]
+pub struct CapturingLayer {
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl CapturingLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The shared buffer of captured events, in the order they were emitted.
+    pub fn buffer(&self) -> Arc<Mutex<Vec<String>>> {
+        self.events.clone()
+    }
+
+    /// A snapshot of the events captured so far, in the order they were emitted.
+    pub fn events(&self) -> Vec<String> {
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for CapturingLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = CaptureVisitor::default();
+        event.record(&mut visitor);
+
+        let meta = event.metadata();
+        let mut line = format!("{} {}: {}", meta.level(), meta.target(), visitor.message);
+
+        for (key, value) in visitor.fields {
+            line.push_str(&format!(" {key}={value}"));
+        }
+
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(line);
+    }
+}
+
+/// Collects the message and remaining fields of an event.
+#[derive(Default)]
+struct CaptureVisitor {
+    message: String,
+    fields: Vec<(&'static str, String)>,
+}
+
+impl Visit for CaptureVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        // record strings without the quotes that their Debug representation adds
+        match field.name() {
+            "message" => self.message = value.to_string(),
+            name => self.fields.push((name, value.to_string())),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{value:?}"),
+            name => self.fields.push((name, format!("{value:?}"))),
+        }
+    }
+}
+
+/// Keeps the subscriber installed by [init_logging_scoped] until dropped, then stops its
+/// [rolling file](LogLevelFilterBuilder::rolling_file) writer, if any, flushing buffered events.
+pub struct ScopedLogging {
+    // fields are dropped in order, so the subscriber is uninstalled before the file is flushed
+    _default: DefaultGuard,
+    _file: Option<WorkerGuard>,
+}
+
+/// Keeps the capturing subscriber installed by [init_logging_capture] until dropped.
+pub struct CaptureHandle {
+    layer: CapturingLayer,
+    _guard: DefaultGuard,
+}
+
+impl CaptureHandle {
+    /// A snapshot of the events captured so far, in the order they were emitted.
+    pub fn events(&self) -> Vec<String> {
+        self.layer.events()
+    }
+}
+
+/// A handle for changing the levels of the installed [LogLevelFilter] at runtime.
+#[derive(Clone)]
+pub struct ReloadHandle {
+    inner: reload::Handle<LogLevelFilter, Registry>,
+}
+
+impl ReloadHandle {
+    /// Set the global level, taking effect immediately.
+    pub fn set_global(&self, level: Level) {
+        self.modify(|f| f.set_global(level));
+    }
+
+    /// Set the level for a given module, taking effect immediately.
+    pub fn filter<S>(&self, logger: S, level: Level)
+    where
+        S: Into<String>,
+    {
+        let logger = logger.into();
+        self.modify(move |f| f.filter(logger, level));
+    }
+
+    fn modify(&self, f: impl FnOnce(&mut LogLevelFilter)) {
+        // this can only fail if the subscriber has been dropped, in which case nothing is logged
+        let _ = self.inner.modify(f);
+    }
+}
+
+impl<S: Subscriber> Filter<S> for LogLevelFilter {
+    fn enabled(&self, meta: &Metadata<'_>, _cx: &Context<'_, S>) -> bool {
+        // the reload layer doesn't forward event_enabled, so the rate limit is applied here
+        self.allow(meta) && self.take_rate_limit(meta)
+    }
+}
+
+/// Errors produced when parsing a `RUST_LOG`-style directive string.
+#[derive(Debug, Clone, Eq, PartialEq, Error)]
+pub enum DirectiveError {
+    /// The environment variable is unset or is not valid unicode.
+    #[error("environment variable {0} is not set or is not valid unicode")]
+    Env(String),
+    /// A directive named a level which doesn't exist.
+    #[error("invalid level {level:?} in directive {directive:?}")]
+    InvalidLevel { directive: String, level: String },
+    /// A directive was neither `level` nor `module=level`.
+    #[error("invalid directive {0:?}, expected `level` or `module=level`")]
+    InvalidDirective(String),
 }
 
 pub struct LogLevelFilter {
     global: Level,
     modules: HashMap<String, Level>,
+    patterns: Vec<(ModulePattern, Level)>,
+    /// Fields added to every log line, sorted by key; taken by the subscriber on initialization.
+    context: Vec<(String, String)>,
+    rate_limit: Option<RateLimiter>,
+    /// Whether to add the `trace_id` and `span_id` of the enclosing span to every log line.
+    trace_ids: bool,
+    /// Whether to color the primary writer's output with ANSI escape sequences.
+    ansi: bool,
+    /// Names of event fields whose values are redacted; taken by the subscriber on initialization.
+    redact: BTreeSet<String>,
+    /// A file to write to alongside the primary writer; taken by the subscriber on initialization.
+    file: Option<RollingFile>,
 }
 
 impl Default for LogLevelFilter {
@@ -68,74 +822,401 @@ impl Default for LogLevelFilter {
 }
 
 impl LogLevelFilter {
+    /// Create a builder with this crate's level preset.
+    ///
+    /// The global level is left unset so that [LogLevelFilterBuilder::global] is the sole source
+    /// of an explicit level; [LogLevelFilterBuilder::build] falls back to the default otherwise.
     pub fn builder() -> LogLevelFilterBuilder {
-        let mut b = LogLevelFilterBuilder {
-            ..Default::default()
-        };
+        let mut b = LogLevelFilterBuilder::default();
 
-        b.global = Some(DEFAULT_LEVEL);
         b.modules
             .insert(env!("CARGO_CRATE_NAME").to_string(), DEFAULT_CRATE_LEVEL);
 
         b
     }
 
+    /// Build a filter from directives in the given environment variable, such as
+    /// `warn,my_crate=debug,my_crate::net=trace`.
+    ///
+    /// See [LogLevelFilter::from_str] for the directive syntax.
+    pub fn from_env(var: &str) -> Result<Self, DirectiveError> {
+        env::var(var)
+            .map_err(|_| DirectiveError::Env(var.to_string()))?
+            .parse()
+    }
+
     pub fn set_global(&mut self, level: Level) {
         self.global = level;
     }
 
+    /// Set the level for a module, or for a family of modules if `logger` contains a `*` glob.
     pub fn filter<S>(&mut self, logger: S, level: Level)
-        where
-            S: Into<String>,
+    where
+        S: Into<String>,
     {
-        self.modules.insert(logger.into(), level);
+        let logger = logger.into();
+
+        match ModulePattern::compile(&logger) {
+            Some(pattern) => {
+                // replace an existing rule for the same pattern rather than shadowing it
+                self.patterns.retain(|(p, _)| *p != pattern);
+                self.patterns.push((pattern, level));
+            }
+            None => {
+                self.modules.insert(logger, level);
+            }
+        }
     }
 
+    /// Limit each module to emitting at most `per_second` events per second, dropping the excess,
+    /// or remove the limit with [None].
+    pub fn set_rate_limit(&mut self, per_second: Option<u32>) {
+        self.rate_limit = per_second.map(RateLimiter::new);
+    }
+
+    /// Whether the event or span is allowed by the configured levels.
+    ///
+    /// This doesn't consult the rate limit, which only the subscriber applies, so checking an event
+    /// here doesn't count towards its module's limit.
     pub fn allow(&self, meta: &Metadata) -> bool {
+        self.allow_module(meta.module_path(), meta.level())
+    }
+
+    /// Take a token from the rate limit of the event's module, returning whether one was available.
+    fn take_rate_limit(&self, meta: &Metadata) -> bool {
+        // spans aren't limited, as dropping them would orphan the events within them
+        match (self.rate_limit.as_ref(), meta.module_path()) {
+            (Some(limiter), Some(module)) if meta.is_event() => limiter.allow(module),
+            _ => true,
+        }
+    }
+
+    fn allow_module(&self, module: Option<&str>, level: &Level) -> bool {
         // NOTE on levels: trace has the _lowest_ possible value in sorting (i.e. 0), while error
         //      has the *highest* possible value in sorting (i.e. 4). thus, in order for us to
         //      determine whether a given level is allowed, we must check `log.level` is greater
         //      than or equal to `log_rule.level`.
-        if let Some(module) = meta.module_path() {
-            let level = successors(Some(module), |m| {
-                m.rsplit_once("::").map(|(head, _tail)| head)
+        if let Some(module) = module {
+            return level <= self.level_for(module);
+        }
+
+        true
+    }
+
+    /// Find the level of the most specific rule matching the module, falling back to the global
+    /// level.
+    ///
+    /// Rules are ranked by how many path segments they span, wildcards included; on a tie, an
+    /// exact module rule wins over a glob pattern.
+    fn level_for(&self, module: &str) -> &Level {
+        // ancestors are walked from the module itself upwards, so the first hit is the deepest
+        let exact = successors(Some(module), |m| {
+            m.rsplit_once("::").map(|(head, _tail)| head)
+        })
+        .find_map(|m| {
+            self.modules
+                .get(m)
+                .map(|level| (m.split("::").count(), level))
+        });
+
+        let glob = self
+            .patterns
+            .iter()
+            .filter(|(pattern, _)| pattern.matches(module))
+            .map(|(pattern, level)| (pattern.specificity(), level))
+            .max_by_key(|(specificity, _)| *specificity);
+
+        match (exact, glob) {
+            (Some((e, level)), Some((g, _))) if e >= g => level,
+            (_, Some((_, level))) => level,
+            (Some((_, level)), None) => level,
+            (None, None) => &self.global,
+        }
+    }
+}
+
+impl FromStr for LogLevelFilter {
+    type Err = DirectiveError;
+
+    /// Parse a comma-separated list of directives: a bare level sets the global level, while
+    /// `module=level` sets the level of a module or glob pattern, as with
+    /// [LogLevelFilterBuilder::level].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut builder = LogLevelFilterBuilder::default();
+
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let (module, level) = match directive.split_once('=') {
+                Some((module, level)) => (Some(module.trim()), level.trim()),
+                None => (None, directive),
+            };
+
+            let parsed = level
+                .parse::<Level>()
+                .map_err(|_| DirectiveError::InvalidLevel {
+                    directive: directive.to_string(),
+                    level: level.to_string(),
+                })?;
+
+            builder = match module {
+                None => builder.global(parsed),
+                Some(module) if !module.is_empty() => builder.level(module, parsed),
+                Some(_) => return Err(DirectiveError::InvalidDirective(directive.to_string())),
+            };
+        }
+
+        Ok(builder.build())
+    }
+}
+
+/// The location and rotation of log files, see [LogLevelFilterBuilder::rolling_file].
+struct RollingFile {
+    directory: PathBuf,
+    prefix: String,
+    rotation: Rotation,
+}
+
+impl RollingFile {
+    fn appender(self) -> RollingFileAppender {
+        RollingFileAppender::new(self.rotation, self.directory, self.prefix)
+    }
+}
+
+/// A token bucket per module, refilled continuously at `per_second` tokens per second and holding
+/// at most one second's worth, so that a module can burst up to its limit before being throttled.
+struct RateLimiter {
+    per_second: u32,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    fn new(per_second: u32) -> Self {
+        Self {
+            per_second,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token from the module's bucket, returning whether one was available.
+    fn allow(&self, module: &str) -> bool {
+        let capacity = f64::from(self.per_second);
+        let now = Instant::now();
+
+        // a panic while holding the lock can't leave a bucket in an invalid state
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(bucket) = buckets.get_mut(module) {
+            return bucket.take(capacity, now);
+        }
+
+        // only allocate the module's name the first time it's seen
+        buckets
+            .entry(module.to_string())
+            .or_insert(TokenBucket {
+                tokens: capacity,
+                updated: now,
             })
-                .find_map(|m| self.modules.get(m))
-                .unwrap_or(&self.global);
+            .take(capacity, now)
+    }
+}
+
+impl TokenBucket {
+    /// Refill the bucket for the time elapsed since it was last updated, then take a token if one
+    /// is available.
+    fn take(&mut self, capacity: f64, now: Instant) -> bool {
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+
+        self.tokens = (self.tokens + elapsed * capacity).min(capacity);
+        self.updated = now;
 
-            return meta.level() <= level;
+        if self.tokens < 1.0 {
+            return false;
         }
 
+        self.tokens -= 1.0;
         true
     }
 }
 
+/// A compiled module glob pattern such as `foo::*` or `*::net`.
+///
+/// Each `*` segment matches one or more module path segments.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct ModulePattern {
+    segments: Vec<Option<String>>,
+}
+
+impl ModulePattern {
+    /// Compile a pattern, returning [None] if it contains no wildcards and is thus a plain module.
+    fn compile(pattern: &str) -> Option<Self> {
+        if !pattern.contains('*') {
+            return None;
+        }
+
+        Some(Self {
+            segments: pattern
+                .split("::")
+                .map(|s| (s != "*").then(|| s.to_string()))
+                .collect(),
+        })
+    }
+
+    /// The number of segments in the pattern, used to rank competing rules.
+    fn specificity(&self) -> usize {
+        self.segments.len()
+    }
+
+    fn matches(&self, module: &str) -> bool {
+        let path: Vec<&str> = module.split("::").collect();
+
+        Self::matches_segments(&self.segments, &path)
+    }
+
+    fn matches_segments(pattern: &[Option<String>], path: &[&str]) -> bool {
+        match (pattern.first(), path.first()) {
+            (None, None) => true,
+            (None, Some(_)) | (Some(_), None) => false,
+            (Some(Some(literal)), Some(segment)) => {
+                literal == segment && Self::matches_segments(&pattern[1..], &path[1..])
+            }
+            // a wildcard consumes at least one segment, then as many more as needed
+            (Some(None), Some(_)) => {
+                (1..=path.len()).any(|n| Self::matches_segments(&pattern[1..], &path[n..]))
+            }
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct LogLevelFilterBuilder {
+    /// The explicitly configured global level, if any.
     global: Option<Level>,
-    #[allow(unused)]
     modules: HashMap<String, Level>,
+    context: HashMap<String, String>,
+    rate_limit: Option<u32>,
+    trace_ids: bool,
+    ansi: Option<bool>,
+    redact: BTreeSet<String>,
+    file: Option<RollingFile>,
 }
 
 impl LogLevelFilterBuilder {
-    #[allow(unused)]
+    /// Set the global level, taking precedence over the default of [Level::WARN].
     pub fn global(mut self, level: Level) -> Self {
         self.global = level.into();
         self
     }
 
+    /// Set the level for a module, or for a family of modules if `logger` contains a `*` glob.
     pub fn level<S>(mut self, logger: S, level: Level) -> Self
-        where
-            S: Into<String>,
+    where
+        S: Into<String>,
     {
         self.modules.insert(logger.into(), level);
         self
     }
 
+    /// Attach key/value context, such as `service` and `version`, to every log line, e.g. to
+    /// identify which program produced it.
+    ///
+    /// Context is fixed once logging is initialized, and is added to any set previously.
+    pub fn with_context(mut self, context: HashMap<String, String>) -> Self {
+        self.context.extend(context);
+        self
+    }
+
+    /// Limit each module to emitting at most `per_second` events per second, dropping the excess
+    /// to keep noisy modules from flooding the output.
+    pub fn rate_limit(mut self, per_second: u32) -> Self {
+        self.rate_limit = Some(per_second);
+        self
+    }
+
+    /// Add OpenTelemetry-style `trace_id` and `span_id` fields to every event within a span, so
+    /// that log lines can be correlated with traces. Events outside of any span carry neither.
+    pub fn trace_ids(mut self, enabled: bool) -> Self {
+        self.trace_ids = enabled;
+        self
+    }
+
+    /// Enable or disable ANSI colors in the primary writer's output, which is otherwise colored
+    /// only if stderr is a terminal and the `NO_COLOR` environment variable isn't set.
+    ///
+    /// Log files never contain colors, see [LogLevelFilterBuilder::rolling_file].
+    pub fn with_ansi(mut self, enabled: bool) -> Self {
+        self.ansi = Some(enabled);
+        self
+    }
+
+    /// Replace the values of event fields with any of the given names, such as `password`, with
+    /// [REDACTED] in all output, to keep secrets out of the logs.
+    ///
+    /// Only the fields of events are redacted, not those of spans. Names are added to any set
+    /// previously.
+    pub fn redact<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.redact.extend(fields.into_iter().map(Into::into));
+        self
+    }
+
+    /// Also write logs to files named `{prefix}.{date}` in `directory`, starting a new file on each
+    /// `rotation`, e.g. [Rotation::DAILY]; the primary writer, such as stderr, is kept.
+    ///
+    /// The file is written on a background thread, see [init_logging_guarded].
+    ///
+    /// # Panics
+    ///
+    /// Logging initialization panics if the directory can't be created or the file can't be opened.
+    pub fn rolling_file<D, P>(mut self, directory: D, prefix: P, rotation: Rotation) -> Self
+    where
+        D: Into<PathBuf>,
+        P: Into<String>,
+    {
+        self.file = Some(RollingFile {
+            directory: directory.into(),
+            prefix: prefix.into(),
+            rotation,
+        });
+        self
+    }
+
+    /// Build the filter, using the default global level unless one was explicitly set.
     pub fn build(self) -> LogLevelFilter {
-        LogLevelFilter {
+        let mut context: Vec<(String, String)> = self.context.into_iter().collect();
+        context.sort();
+
+        let mut filter = LogLevelFilter {
             global: self.global.unwrap_or(DEFAULT_LEVEL),
-            modules: self.modules,
+            modules: HashMap::with_capacity(self.modules.len()),
+            patterns: Vec::new(),
+            context,
+            rate_limit: self.rate_limit.map(RateLimiter::new),
+            trace_ids: self.trace_ids,
+            ansi: self.ansi.unwrap_or_else(default_ansi),
+            redact: self.redact,
+            file: self.file,
+        };
+
+        // compile glob patterns, leaving plain modules as exact rules
+        for (logger, level) in self.modules {
+            filter.filter(logger, level);
         }
+
+        filter
     }
 }
+
+/// Whether to color output by default: only when writing to an interactive terminal, and the user
+/// hasn't opted out with `NO_COLOR`.
+fn default_ansi() -> bool {
+    let no_color = env::var_os(NO_COLOR_VAR).is_some_and(|value| !value.is_empty());
+
+    !no_color && io::stderr().is_terminal()
+}
@@ -0,0 +1,562 @@
+use super::{
+    init_logging_capture, init_logging_scoped_with_writer, init_logging_with_writer, subscriber,
+    DirectiveError, LogFormat, LogLevelFilter, LogLevelFilterBuilder, ReloadHandle, Rotation,
+};
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+use tracing::callsite::Callsite;
+use tracing::metadata::Kind;
+use tracing::{Level, Subscriber};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// A writer appending into a shared buffer, so that tests can inspect log output.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+impl io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for SharedBuffer {
+    type Writer = Self;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// Build a subscriber writing into a shared buffer, returning the buffer and reload handle.
+fn buffered(
+    filter: LogLevelFilter,
+    format: LogFormat,
+) -> (impl Subscriber, ReloadHandle, SharedBuffer) {
+    let buffer = SharedBuffer::default();
+    let (subscriber, handle, _file_guard) = subscriber(filter, format, buffer.clone());
+
+    (subscriber, handle, buffer)
+}
+
+#[test]
+fn test_reload_module_level() {
+    let (subscriber, handle, buffer) = buffered(
+        LogLevelFilter::builder()
+            .level(env!("CARGO_CRATE_NAME"), Level::INFO)
+            .build(),
+        LogFormat::Pretty,
+    );
+
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::debug!("before reload");
+        assert!(!buffer.contents().contains("before reload"));
+
+        handle.filter(module_path!(), Level::DEBUG);
+
+        tracing::debug!("after reload");
+        tracing::trace!("still filtered");
+    });
+
+    let output = buffer.contents();
+
+    assert!(output.contains("after reload"));
+    assert!(!output.contains("before reload"));
+    assert!(!output.contains("still filtered"));
+}
+
+#[test]
+fn test_reload_global_level() {
+    // without any module rules, everything falls back to the global level
+    let (subscriber, handle, buffer) = buffered(
+        LogLevelFilterBuilder::default().global(Level::WARN).build(),
+        LogFormat::Compact,
+    );
+
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::info!("before reload");
+
+        handle.set_global(Level::INFO);
+
+        tracing::info!("after reload");
+    });
+
+    let output = buffer.contents();
+
+    assert!(output.contains("after reload"));
+    assert!(!output.contains("before reload"));
+}
+
+#[test]
+fn test_json_format() {
+    let (subscriber, _handle, buffer) = buffered(LogLevelFilter::default(), LogFormat::Json);
+
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::info!("hello json");
+    });
+
+    let output = buffer.contents();
+    let line = output.lines().next().expect("no log line written");
+    let event: serde_json::Value = serde_json::from_str(line).expect("log line is not valid JSON");
+
+    assert_eq!("INFO", event["level"]);
+    assert_eq!("hello json", event["message"]);
+}
+
+#[test]
+fn test_context_fields() {
+    let filter = || {
+        LogLevelFilter::builder()
+            .with_context(HashMap::from([
+                ("service".to_string(), "logging-tests".to_string()),
+                ("version".to_string(), "1.2.3".to_string()),
+            ]))
+            .build()
+    };
+
+    let (subscriber, _handle, buffer) = buffered(filter(), LogFormat::Compact);
+
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::info!("hello context");
+    });
+
+    let output = buffer.contents();
+    assert!(
+        output.starts_with("service=logging-tests version=1.2.3 "),
+        "{output}"
+    );
+    assert!(output.contains("hello context"), "{output}");
+
+    let (subscriber, _handle, buffer) = buffered(filter(), LogFormat::Json);
+
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::info!("hello json context");
+    });
+
+    let output = buffer.contents();
+    let line = output.lines().next().expect("no log line written");
+    let event: serde_json::Value = serde_json::from_str(line).expect("log line is not valid JSON");
+
+    assert_eq!("logging-tests", event["service"]);
+    assert_eq!("1.2.3", event["version"]);
+    assert_eq!("hello json context", event["message"]);
+}
+
+#[test]
+fn test_scoped_logging_trace() {
+    let buffer = SharedBuffer::default();
+
+    {
+        let _guard = init_logging_scoped_with_writer(
+            LogLevelFilter::builder()
+                .level(env!("CARGO_CRATE_NAME"), Level::TRACE)
+                .build(),
+            LogFormat::Compact,
+            buffer.clone(),
+        );
+
+        tracing::trace!("scoped trace");
+    }
+
+    // once the guard is dropped, events no longer reach the scoped subscriber
+    tracing::error!("after trace guard");
+
+    let output = buffer.contents();
+    assert!(output.contains("scoped trace"), "{output}");
+    assert!(!output.contains("after trace guard"), "{output}");
+}
+
+#[test]
+fn test_scoped_logging_error() {
+    let buffer = SharedBuffer::default();
+
+    {
+        let _guard = init_logging_scoped_with_writer(
+            LogLevelFilter::builder()
+                .level(env!("CARGO_CRATE_NAME"), Level::ERROR)
+                .build(),
+            LogFormat::Compact,
+            buffer.clone(),
+        );
+
+        tracing::trace!("scoped trace");
+        tracing::error!("scoped error");
+    }
+
+    let output = buffer.contents();
+    assert!(!output.contains("scoped trace"), "{output}");
+    assert!(output.contains("scoped error"), "{output}");
+}
+
+#[test]
+fn test_init_logging_with_writer() {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+
+    // this is the only test which installs the global subscriber
+    let (_handle, _guard) = init_logging_with_writer(
+        LogLevelFilter::default(),
+        LogFormat::Compact,
+        SharedBuffer(buffer.clone()),
+    );
+
+    tracing::warn!("captured globally");
+
+    let output = String::from_utf8_lossy(&buffer.lock().unwrap()).into_owned();
+
+    assert!(output.contains("captured globally"));
+    assert!(output.contains("WARN"));
+}
+
+#[test]
+fn test_glob_patterns() {
+    let filter = LogLevelFilter::builder()
+        .global(Level::ERROR)
+        .level("foo::*", Level::INFO)
+        .level("*::net", Level::TRACE)
+        .build();
+
+    // picked up by the family rule
+    assert!(filter.allow_module(Some("foo::bar::baz"), &Level::INFO));
+    assert!(!filter.allow_module(Some("foo::bar::baz"), &Level::DEBUG));
+    // a wildcard matches at least one segment, so the root falls back to the global level
+    assert!(!filter.allow_module(Some("foo"), &Level::INFO));
+    // suffix patterns
+    assert!(filter.allow_module(Some("other::net"), &Level::TRACE));
+    assert!(!filter.allow_module(Some("other::net::tcp"), &Level::TRACE));
+}
+
+#[test]
+fn test_specific_rule_overrides_glob() {
+    let mut filter = LogLevelFilter::builder()
+        .global(Level::ERROR)
+        .level("foo::*", Level::INFO)
+        .level("foo::bar", Level::WARN)
+        .build();
+
+    assert!(!filter.allow_module(Some("foo::bar::baz"), &Level::INFO));
+    assert!(filter.allow_module(Some("foo::bar::baz"), &Level::WARN));
+    assert!(filter.allow_module(Some("foo::qux"), &Level::INFO));
+
+    // a more specific glob wins over a less specific exact rule
+    filter.filter("foo::bar::*", Level::TRACE);
+
+    assert!(filter.allow_module(Some("foo::bar::baz"), &Level::TRACE));
+}
+
+#[test]
+fn test_builder_global_precedence() {
+    let filter = LogLevelFilter::builder().global(Level::INFO).build();
+
+    assert!(filter.allow_module(Some("other"), &Level::INFO));
+    assert!(!filter.allow_module(Some("other"), &Level::DEBUG));
+
+    // without an explicit global level, the default applies
+    let filter = LogLevelFilter::builder().build();
+
+    assert!(filter.allow_module(Some("other"), &Level::WARN));
+    assert!(!filter.allow_module(Some("other"), &Level::INFO));
+}
+
+#[test]
+fn test_parse_directives() {
+    let filter: LogLevelFilter = "warn,my_crate=debug,my_crate::net=trace"
+        .parse()
+        .expect("unable to parse directives");
+
+    assert!(filter.allow_module(Some("other"), &Level::WARN));
+    assert!(!filter.allow_module(Some("other"), &Level::INFO));
+    assert!(filter.allow_module(Some("my_crate::io"), &Level::DEBUG));
+    assert!(!filter.allow_module(Some("my_crate::io"), &Level::TRACE));
+    assert!(filter.allow_module(Some("my_crate::net::tcp"), &Level::TRACE));
+}
+
+#[test]
+fn test_parse_directive_errors() {
+    assert_eq!(
+        Err(DirectiveError::InvalidLevel {
+            directive: "my_crate=loud".into(),
+            level: "loud".into(),
+        }),
+        "info,my_crate=loud".parse::<LogLevelFilter>().map(|_| ())
+    );
+    assert_eq!(
+        Err(DirectiveError::InvalidDirective("=debug".into())),
+        "=debug".parse::<LogLevelFilter>().map(|_| ())
+    );
+}
+
+#[test]
+fn test_from_env() {
+    std::env::set_var("EXAMPLES_COMMON_TEST_LOG", "error,foo::*=info");
+
+    let filter = LogLevelFilter::from_env("EXAMPLES_COMMON_TEST_LOG").expect("unable to parse");
+
+    assert!(!filter.allow_module(Some("other"), &Level::WARN));
+    assert!(filter.allow_module(Some("foo::bar"), &Level::INFO));
+
+    assert_eq!(
+        Err(DirectiveError::Env("EXAMPLES_COMMON_TEST_LOG_UNSET".into())),
+        LogLevelFilter::from_env("EXAMPLES_COMMON_TEST_LOG_UNSET").map(|_| ())
+    );
+}
+
+#[test]
+fn test_rate_limit() {
+    let (subscriber, _handle, buffer) = buffered(
+        LogLevelFilter::builder()
+            .level(module_path!(), Level::INFO)
+            .rate_limit(10)
+            .build(),
+        LogFormat::Compact,
+    );
+
+    tracing::subscriber::with_default(subscriber, || {
+        for i in 0..100 {
+            tracing::info!("flood {i}");
+        }
+    });
+
+    // the bucket starts full, and barely refills while the loop runs
+    let passed = buffer.contents().matches("flood").count();
+
+    assert!((10..=11).contains(&passed), "{passed} events passed");
+}
+
+#[test]
+fn test_allow_ignores_rate_limit() {
+    let filter = LogLevelFilter::builder()
+        .level(module_path!(), Level::INFO)
+        .rate_limit(1)
+        .build();
+
+    let callsite = tracing::callsite!(
+        name: "probe",
+        kind: Kind::EVENT,
+        level: Level::INFO,
+        fields: []
+    );
+
+    // checking the levels alone never takes from the bucket
+    for _ in 0..10 {
+        assert!(filter.allow(callsite.metadata()));
+    }
+}
+
+#[test]
+fn test_capture() {
+    let capture = init_logging_capture();
+
+    tracing::info!("first event");
+    tracing::trace!(attempt = 2, name = "retry", "second event");
+
+    assert_eq!(
+        vec![
+            format!("INFO {}: first event", module_path!()),
+            format!(
+                "TRACE {}: second event attempt=2 name=retry",
+                module_path!()
+            ),
+        ],
+        capture.events()
+    );
+
+    drop(capture);
+
+    // events emitted after the handle is dropped are no longer captured
+    let capture = init_logging_capture();
+    assert!(capture.events().is_empty());
+}
+
+#[test]
+fn test_rolling_file() {
+    let dir = tempfile::tempdir().expect("unable to create temp dir");
+
+    let (subscriber, _handle, guard) = subscriber(
+        LogLevelFilter::builder()
+            .rolling_file(dir.path(), "example.log", Rotation::NEVER)
+            .build(),
+        LogFormat::Compact,
+        SharedBuffer::default(),
+    );
+
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::warn!("first to file");
+        tracing::error!("second to file");
+    });
+
+    // dropping the guard flushes the background writer
+    drop(guard.expect("no file guard returned"));
+
+    let contents =
+        std::fs::read_to_string(dir.path().join("example.log")).expect("log file was not created");
+
+    assert!(contents.contains("first to file"), "{contents}");
+    assert!(contents.contains("second to file"), "{contents}");
+    // files never contain terminal colors
+    assert!(!contents.contains('\x1b'), "{contents}");
+}
+
+#[test]
+fn test_scoped_rolling_file() {
+    let dir = tempfile::tempdir().expect("unable to create temp dir");
+
+    for i in 0..2 {
+        let _guard = init_logging_scoped_with_writer(
+            LogLevelFilter::builder()
+                .level(module_path!(), Level::INFO)
+                .rolling_file(dir.path(), format!("scoped-{i}.log"), Rotation::NEVER)
+                .build(),
+            LogFormat::Compact,
+            SharedBuffer::default(),
+        );
+
+        tracing::info!("scoped to file {i}");
+    }
+
+    // each scope's file is flushed as soon as its guard is dropped
+    for i in 0..2 {
+        let contents = std::fs::read_to_string(dir.path().join(format!("scoped-{i}.log")))
+            .expect("log file was not created");
+
+        assert!(
+            contents.contains(&format!("scoped to file {i}")),
+            "{contents}"
+        );
+    }
+}
+
+#[test]
+fn test_trace_ids() {
+    let (subscriber, _handle, buffer) = buffered(
+        LogLevelFilter::builder()
+            .level(module_path!(), Level::INFO)
+            .trace_ids(true)
+            .build(),
+        LogFormat::Compact,
+    );
+
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::info!("outside");
+
+        let request = tracing::info_span!("request");
+        let _entered = request.enter();
+
+        tracing::info!("first in request");
+        tracing::info!("second in request");
+
+        tracing::info_span!("query").in_scope(|| tracing::info!("in query"));
+    });
+
+    let output = buffer.contents();
+    let ids = |message: &str| {
+        let line = output
+            .lines()
+            .find(|line| line.ends_with(message))
+            .unwrap_or_else(|| panic!("no line for {message:?} in {output}"));
+
+        let field = |key: &str| {
+            line.split_whitespace()
+                .find_map(|pair| pair.strip_prefix(key)?.strip_prefix('='))
+                .map(str::to_string)
+        };
+
+        (field("trace_id"), field("span_id"))
+    };
+
+    assert_eq!((None, None), ids("outside"));
+
+    let (trace_id, span_id) = ids("first in request");
+    let trace_id = trace_id.expect("no trace id in span");
+    let span_id = span_id.expect("no span id in span");
+
+    assert_eq!(32, trace_id.len());
+    assert_eq!(16, span_id.len());
+    assert_eq!(
+        (Some(trace_id.clone()), Some(span_id.clone())),
+        ids("second in request")
+    );
+
+    // a child span shares the trace but not the span id
+    let (child_trace_id, child_span_id) = ids("in query");
+    assert_eq!(Some(trace_id), child_trace_id);
+    assert_ne!(Some(span_id), child_span_id);
+}
+
+#[test]
+fn test_ansi() {
+    let output = |ansi: bool| {
+        let (subscriber, _handle, buffer) = buffered(
+            LogLevelFilter::builder()
+                .level(module_path!(), Level::INFO)
+                .with_ansi(ansi)
+                .build(),
+            LogFormat::Pretty,
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(attempt = 3, "colorful");
+        });
+
+        buffer.contents()
+    };
+
+    let plain = output(false);
+    assert!(plain.contains("colorful"), "{plain}");
+    assert!(!plain.contains('\x1b'), "{plain}");
+
+    let colored = output(true);
+    assert!(colored.contains("colorful"), "{colored}");
+    assert!(colored.contains('\x1b'), "{colored}");
+}
+
+#[test]
+fn test_redact() {
+    let redacted = |format| {
+        let (subscriber, _handle, buffer) = buffered(
+            LogLevelFilter::builder()
+                .level(module_path!(), Level::INFO)
+                .redact(["password", "passphrase", "key"])
+                .build(),
+            format,
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info_span!("login", user = "alice").in_scope(|| {
+                tracing::info!(password = "hunter2", attempt = 3, "logging in");
+            });
+
+            tracing::info!(key = 42, "untouched message");
+        });
+
+        buffer.contents()
+    };
+
+    let output = redacted(LogFormat::Compact);
+
+    assert!(!output.contains("hunter2"), "password leaked: {output}");
+    assert!(output.contains(r#"password="***""#), "{output}");
+    assert!(output.contains("attempt=3"), "{output}");
+    assert!(output.contains("logging in"), "{output}");
+    // the event keeps its place in the span, whose fields aren't redacted
+    assert!(output.contains("INFO login:"), "{output}");
+    assert!(output.contains(r#"user="alice""#), "{output}");
+    assert!(output.contains(r#"key="***""#), "{output}");
+
+    let output = redacted(LogFormat::Json);
+    let line: serde_json::Value = serde_json::from_str(output.lines().next().unwrap()).unwrap();
+
+    assert_eq!("***", line["password"]);
+    assert_eq!(3, line["attempt"]);
+    assert_eq!("logging in", line["message"]);
+}